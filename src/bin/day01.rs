@@ -1,83 +1,348 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use aoc2025::{extract_day_from_exe, read_input};
+use std::{env, fmt, str::FromStr};
 
-const SAFE_DIAL_POS: i32 = 100;
+const SAFE_DIAL_POS: i64 = 100;
+const START_DIAL_POS: i64 = 50;
 
-fn split_at_first_letter(s: &str) -> Option<(String, String)> {
-    let mut chars = s.chars();
-    let first = chars.next()?.to_string();
-    let rest = chars.as_str().to_string();
-    Some((first, rest))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Left,
+    Right,
 }
 
-fn part1(input: &str) -> Result<i32> {
-    let mut times_at_zero = 0;
-    let mut dial_pos = 50;
+/// A line of a dial script: `L<n>`/`R<n>` turn the dial, `S<n>` sets it straight to position `n`.
+/// Blank lines and lines starting with `#` are skipped before parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Rotate(Direction, i64),
+    Set(i64),
+}
+
+impl FromStr for Instruction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let op = chars.next().ok_or_else(|| anyhow!("empty instruction"))?;
+        let amount_str = chars.as_str();
+        let amount = amount_str
+            .parse::<i64>()
+            .ok()
+            .filter(|n| *n >= 0)
+            .ok_or_else(|| anyhow!("invalid amount {:?} in {:?}", amount_str, s))?;
+
+        match op {
+            'L' => Ok(Instruction::Rotate(Direction::Left, amount)),
+            'R' => Ok(Instruction::Rotate(Direction::Right, amount)),
+            'S' => Ok(Instruction::Set(amount)),
+            _ => bail!("unknown operation {:?} in {:?}", op, s),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Rotate(Direction::Left, amount) => write!(f, "L{}", amount),
+            Instruction::Rotate(Direction::Right, amount) => write!(f, "R{}", amount),
+            Instruction::Set(pos) => write!(f, "S{}", pos),
+        }
+    }
+}
+
+/// Parse a dial script, pairing each instruction with its 1-based line number.
+fn parse_instructions(input: &str) -> Result<Vec<(usize, Instruction)>> {
+    let mut instructions: Vec<(usize, Instruction)> = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#") {
+            continue;
+        }
+        let instruction = line
+            .parse::<Instruction>()
+            .map_err(|e| anyhow!("line {}: {}", i + 1, e))?;
+        instructions.push((i + 1, instruction));
+    }
+
+    Ok(instructions)
+}
+
+/// Rotate a dial of `size` positions from `dial_pos` and return the new position along with how
+/// many times the dial pointed at zero on the way, including the final position but not the
+/// starting one.  Splitting `amount` into whole turns plus a remainder keeps `i64::MAX` sized
+/// rotations from overflowing, and the partial turn is added up in `i128` so dials of any size up
+/// to `i64::MAX` are fine too.
+fn rotate(size: i64, dial_pos: i64, dir: Direction, amount: i64) -> (i64, i64) {
+    let full_turns = amount / size;
+    let rest = (amount % size) as i128;
+    let wide_size = size as i128;
+
+    if dir == Direction::Left {
+        // Turning left from `dial_pos` is turning right from its mirror image on the dial
+        let mirrored_pos = (size - dial_pos) % size;
+        let new_pos = (dial_pos as i128 - rest).rem_euclid(wide_size) as i64;
+        let hits = ((mirrored_pos as i128 + rest) / wide_size) as i64;
+        (new_pos, full_turns + hits)
+    } else {
+        let sum = dial_pos as i128 + rest;
+        (
+            (sum % wide_size) as i64,
+            full_turns + (sum / wide_size) as i64,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TargetCount {
+    target: i64,
+    landed_on: i64,
+    /// Kept in `i128`: every rotation can pass the target close to `i64::MAX` times.
+    passed_through: i128,
+}
+
+/// A dial watching several target positions.  "Landed on" only counts rotations that stop on the
+/// target (part 1), "passed through" also counts every time a rotation sweeps over it (part 2).
+#[derive(Debug, Clone)]
+struct Dial {
+    size: i64,
+    pos: i64,
+    counts: Vec<TargetCount>,
+}
+
+impl Dial {
+    fn new(size: i64, start: i64, targets: &[i64]) -> Result<Self> {
+        if size < 1 {
+            bail!("dial size must be positive, got {}", size);
+        }
+        for pos in targets.iter().chain([&start]) {
+            if !(0..size).contains(pos) {
+                bail!("dial position {} is outside a dial of size {}", pos, size);
+            }
+        }
 
-    for line in input.lines() {
-        let (dir, amount) = split_at_first_letter(line)
-            .map(|(first, rest)| (first.to_string(), rest.parse::<i32>().ok()))
-            .and_then(|(s, num)| num.map(|n| (s, n)))
-            .expect("Invalid dial rotation format");
+        let counts = targets
+            .iter()
+            .map(|target| TargetCount {
+                target: *target,
+                landed_on: 0,
+                passed_through: 0,
+            })
+            .collect();
+
+        Ok(Dial {
+            size,
+            pos: start,
+            counts,
+        })
+    }
 
-        if dir == "L" {
-            dial_pos -= amount % SAFE_DIAL_POS;
-            if dial_pos < 0 {
-                dial_pos += SAFE_DIAL_POS;
+    fn rotate(&mut self, dir: Direction, amount: i64) {
+        for count in self.counts.iter_mut() {
+            // Shift the dial so the target sits at zero
+            let relative_pos = (self.pos - count.target).rem_euclid(self.size);
+            let (new_pos, hits) = rotate(self.size, relative_pos, dir, amount);
+            count.passed_through += hits as i128;
+            if new_pos == 0 {
+                count.landed_on += 1;
             }
-        } else {
-            dial_pos += amount % SAFE_DIAL_POS;
-            if dial_pos >= SAFE_DIAL_POS {
-                dial_pos -= SAFE_DIAL_POS;
+        }
+        self.pos = rotate(self.size, self.pos, dir, amount).0;
+    }
+
+    /// Setting the position moves the dial without turning it, so it never counts towards the
+    /// targets.
+    fn apply(&mut self, instruction: Instruction) -> Result<()> {
+        match instruction {
+            Instruction::Rotate(dir, amount) => self.rotate(dir, amount),
+            Instruction::Set(pos) => {
+                if pos >= self.size {
+                    bail!("position {} is outside a dial of size {}", pos, self.size);
+                }
+                self.pos = pos;
             }
         }
-        if dial_pos == 0 {
-            times_at_zero += 1
+        Ok(())
+    }
+
+    fn run(&mut self, input: &str) -> Result<()> {
+        for (line_num, instruction) in parse_instructions(input)? {
+            self.apply(instruction)
+                .map_err(|e| anyhow!("line {}: {}", line_num, e))?;
         }
+        Ok(())
     }
+}
+
+fn part1(input: &str) -> Result<i64> {
+    let mut dial = Dial::new(SAFE_DIAL_POS, START_DIAL_POS, &[0])?;
+    dial.run(input)?;
 
-    Ok(times_at_zero)
+    Ok(dial.counts[0].landed_on)
 }
 
-fn part2(input: &str) -> Result<i32> {
-    let mut times_past_zero = 0;
-    let mut dial_pos = 50;
+fn part2(input: &str) -> Result<i128> {
+    let mut dial = Dial::new(SAFE_DIAL_POS, START_DIAL_POS, &[0])?;
+    dial.run(input)?;
 
-    for line in input.lines() {
-        let (dir, amount) = split_at_first_letter(line)
-            .map(|(first, rest)| (first.to_string(), rest.parse::<i32>().ok()))
-            .and_then(|(s, num)| num.map(|n| (s, n)))
-            .expect("Invalid dial rotation format");
+    Ok(dial.counts[0].passed_through)
+}
 
-        if dir == "L" {
-            if dial_pos == 0 {
-                times_past_zero -= 1 //Don't count if already on zero
-            }
-            dial_pos -= amount;
-            while dial_pos < 0 {
-                dial_pos += SAFE_DIAL_POS;
-                times_past_zero += 1;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TraceFormat {
+    Table,
+    Jsonl,
+}
+
+impl FromStr for TraceFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(TraceFormat::Table),
+            "jsonl" => Ok(TraceFormat::Jsonl),
+            _ => bail!("unknown trace format: {}", s),
+        }
+    }
+}
+
+/// One rotation as seen from the dial's first watched target, which is zero for the puzzle dial.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TraceStep {
+    instruction: String,
+    start: i64,
+    end: i64,
+    hits: i128,
+    total: i128,
+}
+
+fn trace(dial: &mut Dial, input: &str) -> Result<Vec<TraceStep>> {
+    if dial.counts.is_empty() {
+        bail!("tracing needs at least one target position");
+    }
+    let mut steps: Vec<TraceStep> = Vec::new();
+
+    for (line_num, instruction) in parse_instructions(input)? {
+        let start = dial.pos;
+        let total_before = dial.counts[0].passed_through;
+
+        dial.apply(instruction)
+            .map_err(|e| anyhow!("line {}: {}", line_num, e))?;
+        steps.push(TraceStep {
+            instruction: instruction.to_string(),
+            start,
+            end: dial.pos,
+            hits: dial.counts[0].passed_through - total_before,
+            total: dial.counts[0].passed_through,
+        });
+    }
+
+    Ok(steps)
+}
+
+fn format_trace(steps: &[TraceStep], format: TraceFormat) -> String {
+    let mut out = String::new();
+
+    match format {
+        TraceFormat::Table => {
+            out += &format!(
+                "{:<12} {:>6} {:>6} {:>6} {:>8}\n",
+                "instruction", "start", "end", "hits", "total"
+            );
+            for step in steps {
+                out += &format!(
+                    "{:<12} {:>6} {:>6} {:>6} {:>8}\n",
+                    step.instruction, step.start, step.end, step.hits, step.total
+                );
             }
-            if dial_pos == 0 {
-                times_past_zero += 1
+        }
+        TraceFormat::Jsonl => {
+            for step in steps {
+                out += &format!(
+                    "{{\"instruction\":{:?},\"start\":{},\"end\":{},\"hits\":{},\"total\":{}}}\n",
+                    step.instruction, step.start, step.end, step.hits, step.total
+                );
             }
-        } else {
-            dial_pos += amount;
-            while dial_pos >= SAFE_DIAL_POS {
-                times_past_zero += 1;
-                dial_pos -= SAFE_DIAL_POS;
+        }
+    }
+
+    out
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    dial: Option<Dial>,
+    trace: Option<TraceFormat>,
+}
+
+/// Optional custom dial and trace output, e.g. `day01 --size 60 --start 0 --targets 0,15,30` or
+/// `day01 --trace jsonl`.  Dial flags that are left out fall back to the puzzle's dial.
+fn parse_args(args: &[String]) -> Result<Options> {
+    let mut size: Option<i64> = None;
+    let mut start: Option<i64> = None;
+    let mut targets: Option<Vec<i64>> = None;
+    let mut trace: Option<TraceFormat> = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| anyhow!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--size" => size = Some(value.parse()?),
+            "--start" => start = Some(value.parse()?),
+            "--targets" => {
+                targets = Some(
+                    value
+                        .split(",")
+                        .map(|n| n.trim().parse::<i64>())
+                        .collect::<Result<Vec<i64>, _>>()?,
+                )
             }
+            "--trace" => trace = Some(value.parse()?),
+            _ => bail!("unknown argument: {}", arg),
         }
     }
 
-    Ok(times_past_zero)
+    let dial = if size.is_none() && start.is_none() && targets.is_none() {
+        None
+    } else {
+        Some(Dial::new(
+            size.unwrap_or(SAFE_DIAL_POS),
+            start.unwrap_or(START_DIAL_POS),
+            &targets.unwrap_or(vec![0]),
+        )?)
+    };
+
+    Ok(Options { dial, trace })
 }
 
 fn main() -> Result<()> {
     let day = extract_day_from_exe();
     let input = read_input(day);
+    let options = parse_args(&env::args().skip(1).collect::<Vec<String>>())?;
+
+    if let Some(format) = options.trace {
+        let mut dial = match options.dial {
+            Some(dial) => dial,
+            None => Dial::new(SAFE_DIAL_POS, START_DIAL_POS, &[0])?,
+        };
+        print!("{}", format_trace(&trace(&mut dial, &input)?, format));
+        return Ok(());
+    }
 
     println!("Day {:02}", day);
+    if let Some(mut dial) = options.dial {
+        dial.run(&input)?;
+        for count in dial.counts.iter() {
+            println!(
+                "===>Target {}: landed on {}, passed through {}",
+                count.target, count.landed_on, count.passed_through
+            );
+        }
+        return Ok(());
+    }
     println!("===>Part 1: {}", part1(&input)?);
     println!("===>Part 2: {}", part2(&input)?);
 
@@ -100,4 +365,160 @@ mod tests {
         let input = read_example(extract_day_from_exe());
         assert_eq!(part2(&input).unwrap(), 6);
     }
+
+    /// One click at a time, the way the original while-loop version counted.
+    fn rotate_step_by_step(mut dial_pos: i64, dir: Direction, amount: i64) -> (i64, i64) {
+        let step = if dir == Direction::Left { -1 } else { 1 };
+        let mut zero_hits = 0;
+        for _ in 0..amount {
+            dial_pos = (dial_pos + step).rem_euclid(SAFE_DIAL_POS);
+            if dial_pos == 0 {
+                zero_hits += 1;
+            }
+        }
+        (dial_pos, zero_hits)
+    }
+
+    #[test]
+    fn test_rotate_matches_simulation() {
+        // Small LCG so the test is deterministic without pulling in a property testing crate
+        let mut seed = 0x2545f4914f6cdd1du64;
+        let mut next = |max: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) % max) as i64
+        };
+
+        for _ in 0..10_000 {
+            let dial_pos = next(SAFE_DIAL_POS as u64);
+            let dir = if next(2) == 0 {
+                Direction::Left
+            } else {
+                Direction::Right
+            };
+            let amount = next(1_000);
+            assert_eq!(
+                rotate(SAFE_DIAL_POS, dial_pos, dir, amount),
+                rotate_step_by_step(dial_pos, dir, amount),
+                "{} {:?} {}",
+                dial_pos,
+                dir,
+                amount
+            );
+        }
+    }
+
+    #[test]
+    fn test_rotate_huge_amounts() {
+        assert_eq!(
+            rotate(SAFE_DIAL_POS, 50, Direction::Right, i64::MAX),
+            (57, i64::MAX / 100)
+        );
+        assert_eq!(
+            rotate(SAFE_DIAL_POS, 0, Direction::Left, i64::MAX),
+            (93, i64::MAX / 100)
+        );
+        assert_eq!(
+            rotate(SAFE_DIAL_POS, 7, Direction::Left, i64::MAX),
+            (0, i64::MAX / 100 + 1)
+        );
+
+        // Dials too big to add a position and a partial turn in `i64`
+        assert_eq!(
+            rotate(i64::MAX, i64::MAX - 1, Direction::Right, i64::MAX - 1),
+            (i64::MAX - 2, 1)
+        );
+        assert_eq!(rotate(i64::MAX, 1, Direction::Left, i64::MAX - 1), (2, 1));
+    }
+
+    #[test]
+    fn test_counts_past_i64() {
+        let script = "R9223372036854775807\n".repeat(2);
+        let mut dial = Dial::new(1, 0, &[0]).unwrap();
+        dial.run(&script).unwrap();
+        assert_eq!(dial.counts[0].passed_through, 2 * i64::MAX as i128);
+
+        let steps = trace(&mut Dial::new(1, 0, &[0]).unwrap(), &script).unwrap();
+        assert_eq!(steps[1].hits, i64::MAX as i128);
+        assert_eq!(steps[1].total, 2 * i64::MAX as i128);
+
+        // 101 of them on the puzzle dial add up to more than `i64::MAX` too
+        let total = part2(&"R9223372036854775807\n".repeat(101)).unwrap();
+        assert!(total > i64::MAX as i128);
+    }
+
+    #[test]
+    fn test_dial_targets() {
+        let mut dial = Dial::new(10, 0, &[0, 5, 9]).unwrap();
+        dial.rotate(Direction::Right, 15);
+        dial.rotate(Direction::Left, 6);
+        assert_eq!(dial.pos, 9);
+        assert_eq!(
+            dial.counts,
+            vec![
+                TargetCount {
+                    target: 0,
+                    landed_on: 0,
+                    passed_through: 2
+                },
+                TargetCount {
+                    target: 5,
+                    landed_on: 1,
+                    passed_through: 2
+                },
+                TargetCount {
+                    target: 9,
+                    landed_on: 1,
+                    passed_through: 2
+                },
+            ]
+        );
+
+        assert!(Dial::new(10, 10, &[0]).is_err());
+        assert!(Dial::new(10, 0, &[-1]).is_err());
+        assert!(Dial::new(0, 0, &[]).is_err());
+    }
+
+    #[test]
+    fn test_trace() {
+        let input = read_example(extract_day_from_exe());
+        let mut dial = Dial::new(SAFE_DIAL_POS, START_DIAL_POS, &[0]).unwrap();
+        let steps = trace(&mut dial, &input).unwrap();
+
+        assert_eq!(steps.len(), 10);
+        assert_eq!(steps.last().unwrap().total, 6);
+        // L30 from 82 lands on 52 without touching zero, R48 then lands on zero
+        assert_eq!((steps[1].start, steps[1].end, steps[1].hits), (82, 52, 0));
+        assert_eq!((steps[2].start, steps[2].end, steps[2].hits), (52, 0, 1));
+        // L5 starts on zero, which must not be counted again
+        assert_eq!((steps[3].start, steps[3].end, steps[3].hits), (0, 95, 0));
+
+        assert_eq!(
+            format_trace(&steps[..1], TraceFormat::Jsonl),
+            "{\"instruction\":\"L68\",\"start\":50,\"end\":82,\"hits\":1,\"total\":1}\n"
+        );
+    }
+
+    #[test]
+    fn test_instruction_script() {
+        let script = "# open the lock\nR60\n\nS0\n  L100  \n";
+        let mut dial = Dial::new(SAFE_DIAL_POS, START_DIAL_POS, &[0]).unwrap();
+        dial.run(script).unwrap();
+        assert_eq!(dial.pos, 0);
+        assert_eq!(dial.counts[0].passed_through, 2);
+        assert_eq!(dial.counts[0].landed_on, 1);
+
+        let err = parse_instructions("L10\nX10\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: unknown operation 'X' in \"X10\"");
+        assert!(parse_instructions("R1x").is_err());
+        assert!(parse_instructions("L-5").is_err());
+
+        let mut dial = Dial::new(SAFE_DIAL_POS, START_DIAL_POS, &[0]).unwrap();
+        let err = dial.run("R1\nS100").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2: position 100 is outside a dial of size 100"
+        );
+    }
 }
@@ -1,103 +1,357 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use aoc2025::{extract_day_from_exe, read_input};
+use std::{env, str::FromStr};
 
-fn chunk_string(s: &str, size: usize) -> Vec<String> {
-    s.chars()
-        .collect::<Vec<_>>()
-        .chunks(size)
-        .map(|chunk| chunk.iter().collect())
-        .collect()
+/// How an ID's digits must repeat for the ID to be considered invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RepeatPolicy {
+    /// A single block repeated exactly `k` times (part 1 is `Exactly(2)`).
+    Exactly(usize),
+    /// A single block repeated `k` or more times (part 2 is `AtLeast(2)`).
+    AtLeast(usize),
+    /// The digits read the same in both directions, e.g. `12321` or `4554`.
+    Palindrome,
 }
 
-fn split_string(s: &str, size: usize) -> (String, String) {
-    let mut chars = s.chars();
-    let start: String = chars.by_ref().take(size).collect();
-    let rest: String = chars.collect();
+impl FromStr for RepeatPolicy {
+    type Err = anyhow::Error;
 
-    (start, rest)
-}
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, count) = s.split_once(":").unwrap_or((s, ""));
+        let parse_count = || {
+            count
+                .parse::<usize>()
+                .map_err(|_| anyhow!("invalid repeat count in policy: {}", s))
+        };
 
-fn valid_id_part1(id: i64) -> bool {
-    let str_id = id.to_string();
+        match name {
+            "exactly" => Ok(RepeatPolicy::Exactly(parse_count()?)),
+            "at-least" => Ok(RepeatPolicy::AtLeast(parse_count()?)),
+            "palindrome" => Ok(RepeatPolicy::Palindrome),
+            _ => bail!("unknown repeat policy: {}", s),
+        }
+    }
+}
 
-    let (start, rest) = split_string(&str_id, str_id.len() / 2);
-    !start.eq(&rest)
+#[derive(Debug, Clone, Copy)]
+struct IdRule {
+    base: u32,
+    policy: RepeatPolicy,
 }
 
-fn valid_id_part2(id: i64) -> bool {
-    let str_id = id.to_string();
+impl IdRule {
+    fn new(base: u32, policy: RepeatPolicy) -> Result<Self> {
+        if !(2..=36).contains(&base) {
+            bail!("base must be between 2 and 36, got {}", base);
+        }
+        match policy {
+            RepeatPolicy::Exactly(k) | RepeatPolicy::AtLeast(k) if k < 2 => {
+                bail!("repeat count must be at least 2, got {}", k)
+            }
+            _ => {}
+        }
+        Ok(IdRule { base, policy })
+    }
 
-    // dbg!(&str_id, &str_id.len() / 2);
+    fn is_invalid(&self, id: u128) -> bool {
+        self.repeat_count(&to_digits(id, self.base)).is_some()
+    }
 
-    for size in 1..(str_id.len() / 2 + 1) {
-        let chunks = chunk_string(&str_id, size);
-        let mut is_valid = false;
-        for chunk in &chunks[1..] {
-            if !chunks[0].eq(chunk) {
-                is_valid = true;
-                break;
+    /// Number of times the repeating block occurs when `digits` breaks the rule.  `AtLeast` picks
+    /// the smallest block, and a palindrome is reported as the whole ID occurring once.
+    fn repeat_count(&self, digits: &[u8]) -> Option<usize> {
+        match self.policy {
+            RepeatPolicy::Exactly(k) => is_repeated(digits, k).then_some(k),
+            RepeatPolicy::AtLeast(k) => (k..=digits.len()).rev().find(|r| is_repeated(digits, *r)),
+            RepeatPolicy::Palindrome => {
+                (digits.len() >= 2 && digits.iter().eq(digits.iter().rev())).then_some(1)
             }
         }
-        if !is_valid {
-            return false;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            _ => bail!("unknown report format: {}", s),
         }
     }
-    true
 }
 
-fn process_id_range(min_id: i64, max_id: i64, valid_id: fn(i64) -> bool) -> i64 {
-    let mut total = 0i64;
-    // dbg!(("id range ", min_id, max_id));
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InvalidId {
+    id: u128,
+    block: String,
+    repeats: usize,
+}
 
-    for id in min_id..=max_id {
-        if valid_id(id) {
-            // dbg!(("valid: ", id));
-        } else {
-            // dbg!(("invalid: ", id));
-            total += id;
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RangeReport {
+    min_id: u128,
+    max_id: u128,
+    invalid: Vec<InvalidId>,
+    subtotal: u128,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct IdAudit {
+    sum: u128,
+    count: usize,
+}
+
+/// Most significant digit first, so the digit order matches how the ID is written.
+fn to_digits(mut id: u128, base: u32) -> Vec<u8> {
+    let base = base as u128;
+    let mut digits: Vec<u8> = Vec::new();
+
+    loop {
+        digits.push((id % base) as u8);
+        id /= base;
+        if id == 0 {
+            break;
         }
     }
+    digits.reverse();
+    digits
+}
 
+/// True when `digits` is one block repeated exactly `repeats` times.
+fn is_repeated(digits: &[u8], repeats: usize) -> bool {
+    if repeats < 2 || !digits.len().is_multiple_of(repeats) {
+        return false;
+    }
+    let size = digits.len() / repeats;
+    let (first, rest) = digits.split_at(size);
+
+    rest.chunks(size).all(|chunk| chunk == first)
+}
+
+fn digits_to_string(digits: &[u8]) -> String {
+    digits
+        .iter()
+        .map(|d| char::from_digit(*d as u32, 36).unwrap())
+        .collect()
+}
+
+/// Ranges are separated by commas and/or newlines, and whitespace around any of the separators is
+/// ignored, so a trailing newline or `11-22, 95-115` both parse.
+fn parse_ranges(input: &str) -> Result<Vec<(u128, u128)>> {
+    let mut ranges: Vec<(u128, u128)> = Vec::new();
+
+    for range in input.split([',', '\n']).map(str::trim) {
+        if range.is_empty() {
+            continue;
+        }
+        let (min_str, max_str) = range
+            .split_once("-")
+            .ok_or_else(|| anyhow!("invalid ID range, expected <min>-<max>: {}", range))?;
+        let parse_id = |n: &str| {
+            n.trim()
+                .parse::<u128>()
+                .map_err(|e| anyhow!("invalid ID {:?} in range {}: {}", n.trim(), range, e))
+        };
+        ranges.push((parse_id(min_str)?, parse_id(max_str)?));
+    }
+
+    Ok(ranges)
+}
+
+fn add_id(total: u128, id: u128) -> Result<u128> {
     total
+        .checked_add(id)
+        .ok_or_else(|| anyhow!("sum of invalid IDs overflows u128 when adding {}", id))
 }
 
-fn part1(input: &str) -> Result<i64> {
-    let mut total = 0i64;
-    let id_ranges: Vec<Vec<i64>> = input
-        .split(",")
-        .map(|ids| ids.split("-").map(|n| n.parse::<i64>().unwrap()).collect())
-        .collect();
+fn process_id_range(min_id: u128, max_id: u128, rule: &IdRule) -> Result<IdAudit> {
+    let mut audit = IdAudit::default();
+
+    for id in min_id..=max_id {
+        if rule.is_invalid(id) {
+            audit.sum = add_id(audit.sum, id)?;
+            audit.count += 1;
+        }
+    }
 
-    // dbg!(&id_ranges);
+    Ok(audit)
+}
 
-    for ids in id_ranges.iter() {
-        total += process_id_range(ids[0], ids[1], valid_id_part1);
+fn audit_ranges(ranges: &[(u128, u128)], rule: &IdRule) -> Result<IdAudit> {
+    let mut audit = IdAudit::default();
+
+    for (min_id, max_id) in ranges.iter() {
+        let range_audit = process_id_range(*min_id, *max_id, rule)?;
+        audit.sum = add_id(audit.sum, range_audit.sum)?;
+        audit.count += range_audit.count;
     }
 
-    Ok(total)
+    Ok(audit)
 }
 
-fn part2(input: &str) -> Result<i64> {
-    let mut total = 0i64;
-    let id_ranges: Vec<Vec<i64>> = input
-        .split(",")
-        .map(|ids| ids.split("-").map(|n| n.parse::<i64>().unwrap()).collect())
-        .collect();
+fn report_range(min_id: u128, max_id: u128, rule: &IdRule) -> Result<RangeReport> {
+    let mut invalid: Vec<InvalidId> = Vec::new();
 
-    // dbg!(&id_ranges);
+    for id in min_id..=max_id {
+        let digits = to_digits(id, rule.base);
+        if let Some(repeats) = rule.repeat_count(&digits) {
+            invalid.push(InvalidId {
+                id,
+                block: digits_to_string(&digits[..digits.len() / repeats]),
+                repeats,
+            });
+        }
+    }
 
-    for ids in id_ranges.iter() {
-        total += process_id_range(ids[0], ids[1], valid_id_part2);
+    let mut subtotal = 0u128;
+    for i in invalid.iter() {
+        subtotal = add_id(subtotal, i.id)?;
     }
 
-    Ok(total)
+    Ok(RangeReport {
+        min_id,
+        max_id,
+        invalid,
+        subtotal,
+    })
+}
+
+fn format_report(reports: &[RangeReport], format: ReportFormat) -> String {
+    let mut out = String::new();
+
+    match format {
+        ReportFormat::Text => {
+            for report in reports {
+                out += &format!(
+                    "{}-{}: {} invalid, subtotal {}\n",
+                    report.min_id,
+                    report.max_id,
+                    report.invalid.len(),
+                    report.subtotal
+                );
+                for invalid in &report.invalid {
+                    out += &format!(
+                        "    {} = \"{}\" x {}\n",
+                        invalid.id, invalid.block, invalid.repeats
+                    );
+                }
+            }
+        }
+        ReportFormat::Json => {
+            let ranges: Vec<String> = reports
+                .iter()
+                .map(|report| {
+                    let invalid: Vec<String> = report
+                        .invalid
+                        .iter()
+                        .map(|i| {
+                            format!(
+                                "{{\"id\":{},\"block\":\"{}\",\"repeats\":{}}}",
+                                i.id, i.block, i.repeats
+                            )
+                        })
+                        .collect();
+                    format!(
+                        "{{\"min\":{},\"max\":{},\"invalid\":[{}],\"subtotal\":{}}}",
+                        report.min_id,
+                        report.max_id,
+                        invalid.join(","),
+                        report.subtotal
+                    )
+                })
+                .collect();
+            out += &format!("[{}]\n", ranges.join(","));
+        }
+    }
+
+    out
+}
+
+fn part1(input: &str) -> Result<u128> {
+    let rule = IdRule::new(10, RepeatPolicy::Exactly(2))?;
+
+    Ok(audit_ranges(&parse_ranges(input)?, &rule)?.sum)
+}
+
+fn part2(input: &str) -> Result<u128> {
+    let rule = IdRule::new(10, RepeatPolicy::AtLeast(2))?;
+
+    Ok(audit_ranges(&parse_ranges(input)?, &rule)?.sum)
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    rule: Option<IdRule>,
+    report: Option<ReportFormat>,
+}
+
+/// Optional custom audit, e.g. `day02 --base 16 --policy at-least:3 --report json`.  Either rule
+/// flag may be left out, defaulting to base 10 and the part 2 policy.
+fn parse_args(args: &[String]) -> Result<Options> {
+    let mut base: Option<u32> = None;
+    let mut policy: Option<RepeatPolicy> = None;
+    let mut report: Option<ReportFormat> = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| anyhow!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--base" => base = Some(value.parse()?),
+            "--policy" => policy = Some(value.parse()?),
+            "--report" => report = Some(value.parse()?),
+            _ => bail!("unknown argument: {}", arg),
+        }
+    }
+
+    let rule = if base.is_none() && policy.is_none() {
+        None
+    } else {
+        Some(IdRule::new(
+            base.unwrap_or(10),
+            policy.unwrap_or(RepeatPolicy::AtLeast(2)),
+        )?)
+    };
+
+    Ok(Options { rule, report })
 }
 
 fn main() -> Result<()> {
     let day = extract_day_from_exe();
     let input = read_input(day);
+    let options = parse_args(&env::args().skip(1).collect::<Vec<String>>())?;
+
+    if let Some(format) = options.report {
+        let rule = match options.rule {
+            Some(rule) => rule,
+            None => IdRule::new(10, RepeatPolicy::AtLeast(2))?,
+        };
+        let reports = parse_ranges(&input)?
+            .iter()
+            .map(|(min_id, max_id)| report_range(*min_id, *max_id, &rule))
+            .collect::<Result<Vec<RangeReport>>>()?;
+        print!("{}", format_report(&reports, format));
+        return Ok(());
+    }
 
     println!("Day {:02}", day);
+    if let Some(rule) = options.rule {
+        let audit = audit_ranges(&parse_ranges(&input)?, &rule)?;
+        println!(
+            "===>Audit {:?}: {} invalid, sum {}",
+            rule, audit.count, audit.sum
+        );
+        return Ok(());
+    }
     println!("===>Part 1: {}", part1(&input)?);
     println!("===>Part 2: {}", part2(&input)?);
 
@@ -120,4 +374,93 @@ mod tests {
         let input = read_example(extract_day_from_exe());
         assert_eq!(part2(&input).unwrap(), 4174379265);
     }
+
+    #[test]
+    fn test_id_rules() {
+        let exactly_three = IdRule::new(10, RepeatPolicy::Exactly(3)).unwrap();
+        assert!(exactly_three.is_invalid(121212));
+        assert!(!exactly_three.is_invalid(1212));
+
+        // 0b101101 is "101" twice, 0b111 is "1" three times
+        let binary = IdRule::new(2, RepeatPolicy::Exactly(2)).unwrap();
+        assert!(binary.is_invalid(0b101101));
+        assert!(!binary.is_invalid(0b111));
+
+        let palindrome = IdRule::new(16, RepeatPolicy::Palindrome).unwrap();
+        assert!(palindrome.is_invalid(0xabba));
+        assert!(!palindrome.is_invalid(0xab));
+
+        assert!(IdRule::new(37, RepeatPolicy::Palindrome).is_err());
+        assert_eq!(
+            "at-least:3".parse::<RepeatPolicy>().unwrap(),
+            RepeatPolicy::AtLeast(3)
+        );
+        assert!("twice".parse::<RepeatPolicy>().is_err());
+        assert!(IdRule::new(10, RepeatPolicy::AtLeast(1)).is_err());
+    }
+
+    #[test]
+    fn test_audit_count() {
+        let rule = IdRule::new(10, RepeatPolicy::Exactly(2)).unwrap();
+        let audit = audit_ranges(&[(11, 22), (95, 115)], &rule).unwrap();
+        assert_eq!(
+            audit,
+            IdAudit {
+                sum: 11 + 22 + 99,
+                count: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_report_range() {
+        let rule = IdRule::new(10, RepeatPolicy::AtLeast(2)).unwrap();
+        let report = report_range(95, 115, &rule).unwrap();
+        assert_eq!(report.subtotal, 99 + 111);
+        assert_eq!(
+            report.invalid,
+            vec![
+                InvalidId {
+                    id: 99,
+                    block: "9".to_string(),
+                    repeats: 2
+                },
+                InvalidId {
+                    id: 111,
+                    block: "1".to_string(),
+                    repeats: 3
+                },
+            ]
+        );
+        assert_eq!(
+            format_report(&[report], ReportFormat::Json),
+            concat!(
+                "[{\"min\":95,\"max\":115,\"invalid\":[",
+                "{\"id\":99,\"block\":\"9\",\"repeats\":2},",
+                "{\"id\":111,\"block\":\"1\",\"repeats\":3}",
+                "],\"subtotal\":210}]\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        let ranges = parse_ranges(" 11-22, 95 - 115,\n998-1012\n\n").unwrap();
+        assert_eq!(ranges, vec![(11, 22), (95, 115), (998, 1012)]);
+
+        let big = parse_ranges("99999999999999999999-100000000000000000000").unwrap();
+        assert_eq!(big, vec![(99999999999999999999, 100000000000000000000)]);
+
+        assert!(parse_ranges("11-22,95").is_err());
+        assert!(parse_ranges("11-2x").is_err());
+    }
+
+    #[test]
+    fn test_sum_overflow() {
+        let rule = IdRule::new(10, RepeatPolicy::AtLeast(2)).unwrap();
+        let id = 111111111111111111111111111111111111111u128;
+        assert!(rule.is_invalid(id));
+        let err = audit_ranges(&[(id, id), (id, id), (id, id), (id, id)], &rule).unwrap_err();
+        assert!(err.to_string().contains("overflows"));
+    }
 }
@@ -1,57 +1,262 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use aoc2025::{extract_day_from_exe, read_input};
+use std::{env, fmt, ops::AddAssign, str::FromStr};
 
-fn calc_line_voltage(line: &str, num_of_batteries: usize) -> i64 {
-    let mut total = 0i64;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pick {
+    Largest,
+    Smallest,
+}
 
-    let digits: Vec<i8> = line
-        .chars()
-        .map(|c| c.to_digit(10).unwrap() as i8)
-        .collect();
-    let mut battery_list: Vec<i8> = vec![-1; num_of_batteries];
-    let mut starting_pos = 0;
-    for i in 0..battery_list.len() {
-        for x in starting_pos..digits.len() - num_of_batteries + i + 1 {
-            if digits[x] > battery_list[i] {
-                starting_pos = x + 1;
-                battery_list[i] = digits[x];
+/// Pick the positions of the `k` digit subsequence of `line` that forms the largest (or smallest)
+/// number.  Uses a monotonic stack: a digit knocks out the digits before it while it beats them and
+/// there are still digits left to drop, which makes it O(n) no matter how large `k` is.
+fn pick_positions(line: &str, k: usize, pick: Pick) -> Result<Vec<usize>> {
+    let digits: Vec<char> = line.chars().collect();
+    if let Some(pos) = digits.iter().position(|c| !c.is_ascii_digit()) {
+        bail!(
+            "non-digit {:?} at position {} in {:?}",
+            digits[pos],
+            pos,
+            line
+        );
+    }
+    if digits.len() < k {
+        bail!("need {} digits but {:?} only has {}", k, line, digits.len());
+    }
+
+    let mut to_drop = digits.len() - k;
+    let mut stack: Vec<usize> = Vec::with_capacity(digits.len());
+    for (i, digit) in digits.iter().enumerate() {
+        while let Some(top) = stack.last() {
+            let beats_top = match pick {
+                Pick::Largest => *digit > digits[*top],
+                Pick::Smallest => *digit < digits[*top],
+            };
+            if to_drop == 0 || !beats_top {
+                break;
             }
+            stack.pop();
+            to_drop -= 1;
         }
+        stack.push(i);
     }
+    stack.truncate(k);
 
-    let mut multiplier = 10i64.pow(num_of_batteries as u32 - 1);
-    for digit in battery_list.iter() {
-        total += *digit as i64 * multiplier;
-        multiplier /= 10;
+    Ok(stack)
+}
+
+fn pick_digits(line: &str, k: usize, pick: Pick) -> Result<String> {
+    let positions = pick_positions(line, k, pick)?;
+    let bytes = line.as_bytes();
+
+    Ok(positions.iter().map(|i| bytes[*i] as char).collect())
+}
+
+impl FromStr for Pick {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "largest" => Ok(Pick::Largest),
+            "smallest" => Ok(Pick::Smallest),
+            _ => bail!("unknown pick: {}", s),
+        }
     }
-    total
 }
 
-fn part1(input: &str) -> Result<i64> {
-    let mut total = 0i64;
+/// Arbitrary-precision decimal so any number of batteries can be summed without overflowing.
+/// Digits are stored least significant first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Joltage {
+    digits: Vec<u8>,
+}
 
-    for line in input.lines() {
-        total += calc_line_voltage(line, 2);
+impl Joltage {
+    /// `s` must only contain ASCII digits, which `pick_digits` guarantees.
+    fn from_digits(s: &str) -> Self {
+        Joltage {
+            digits: s.bytes().rev().map(|b| b - b'0').collect(),
+        }
     }
+}
 
-    Ok(total)
+impl AddAssign<&Joltage> for Joltage {
+    fn add_assign(&mut self, other: &Joltage) {
+        let mut carry = 0u8;
+        let len = self.digits.len().max(other.digits.len());
+        self.digits.resize(len, 0);
+
+        for (i, digit) in self.digits.iter_mut().enumerate() {
+            let sum = *digit + other.digits.get(i).copied().unwrap_or(0) + carry;
+            *digit = sum % 10;
+            carry = sum / 10;
+        }
+        if carry > 0 {
+            self.digits.push(carry);
+        }
+    }
+}
+
+impl fmt::Display for Joltage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits: String = self
+            .digits
+            .iter()
+            .rev()
+            .skip_while(|d| **d == 0)
+            .map(|d| (b'0' + d) as char)
+            .collect();
+
+        if digits.is_empty() {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", digits)
+        }
+    }
 }
 
-fn part2(input: &str) -> Result<i64> {
-    let mut total = 0i64;
+fn calc_line_voltage(line: &str, num_of_batteries: usize, pick: Pick) -> Result<Joltage> {
+    let digits = pick_digits(line, num_of_batteries, pick)?;
+
+    Ok(Joltage::from_digits(&digits))
+}
+
+fn total_voltage(input: &str, num_of_batteries: usize, pick: Pick) -> Result<Joltage> {
+    let mut total = Joltage::default();
 
     for line in input.lines() {
-        total += calc_line_voltage(line, 12);
+        total += &calc_line_voltage(line, num_of_batteries, pick)?;
     }
 
     Ok(total)
 }
 
+fn part1(input: &str) -> Result<Joltage> {
+    total_voltage(input, 2, Pick::Largest)
+}
+
+fn part2(input: &str) -> Result<Joltage> {
+    total_voltage(input, 12, Pick::Largest)
+}
+
+/// Lines describing how one bank was picked for `k` batteries: the bank, a `^` under every chosen
+/// battery, the resulting number and the chosen indices.
+fn explain_column(line: &str, k: usize, pick: Pick) -> Result<Vec<String>> {
+    let positions = pick_positions(line, k, pick)?;
+    let markers: String = (0..line.len())
+        .map(|i| if positions.contains(&i) { '^' } else { ' ' })
+        .collect();
+    let indices: Vec<String> = positions.iter().map(|i| i.to_string()).collect();
+
+    Ok(vec![
+        line.to_string(),
+        markers.trim_end().to_string(),
+        format!("= {}", pick_digits(line, k, pick)?),
+        format!("at {}", indices.join(",")),
+    ])
+}
+
+/// Every bank explained once per battery count, with the counts laid out side by side.
+fn explain(input: &str, battery_counts: &[usize], pick: Pick) -> Result<String> {
+    let mut out = String::new();
+
+    for (bank, line) in input.lines().enumerate() {
+        let columns = battery_counts
+            .iter()
+            .map(|k| explain_column(line, *k, pick))
+            .collect::<Result<Vec<Vec<String>>>>()?;
+        let headers: Vec<String> = battery_counts
+            .iter()
+            .map(|k| format!("{} batteries", k))
+            .collect();
+        let widths: Vec<usize> = columns
+            .iter()
+            .zip(headers.iter())
+            .map(|(col, header)| col.iter().chain([header]).map(|l| l.len()).max().unwrap())
+            .collect();
+
+        out += &format!("Bank {}:\n", bank + 1);
+        let rows = [headers].into_iter().chain((0..4).map(|row| {
+            columns
+                .iter()
+                .map(|col| col[row].clone())
+                .collect::<Vec<String>>()
+        }));
+        for row in rows {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            out += &format!("  {}\n", cells.join(" | ").trim_end());
+        }
+    }
+
+    Ok(out)
+}
+
+#[derive(Debug)]
+struct Options {
+    num_of_batteries: Option<usize>,
+    pick: Pick,
+    explain: bool,
+}
+
+/// Optional custom selection, e.g. `day03 --batteries 5 --pick smallest`, and `--explain` to show
+/// how each bank was picked.  Without `--batteries` the explanation shows part 1 and part 2.
+fn parse_args(args: &[String]) -> Result<Options> {
+    let mut options = Options {
+        num_of_batteries: None,
+        pick: Pick::Largest,
+        explain: false,
+    };
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "--explain" {
+            options.explain = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| anyhow!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--batteries" => options.num_of_batteries = Some(value.parse()?),
+            "--pick" => options.pick = value.parse()?,
+            _ => bail!("unknown argument: {}", arg),
+        }
+    }
+
+    Ok(options)
+}
+
 fn main() -> Result<()> {
     let day = extract_day_from_exe();
     let input = read_input(day);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = parse_args(&args)?;
+
+    if options.explain {
+        let battery_counts = match options.num_of_batteries {
+            Some(n) => vec![n],
+            None => vec![2, 12],
+        };
+        print!("{}", explain(&input, &battery_counts, options.pick)?);
+        return Ok(());
+    }
 
     println!("Day {:02}", day);
+    if !args.is_empty() {
+        let num_of_batteries = options.num_of_batteries.unwrap_or(12);
+        println!(
+            "===>{:?} {} batteries: {}",
+            options.pick,
+            num_of_batteries,
+            total_voltage(&input, num_of_batteries, options.pick)?
+        );
+        return Ok(());
+    }
     println!("===>Part 1: {}", part1(&input)?);
     println!("===>Part 2: {}", part2(&input)?);
 
@@ -66,12 +271,63 @@ mod tests {
     #[test]
     fn test_part1() {
         let input = read_example(extract_day_from_exe());
-        assert_eq!(part1(&input).unwrap(), 357);
+        assert_eq!(part1(&input).unwrap().to_string(), "357");
     }
 
     #[test]
     fn test_part2() {
         let input = read_example(extract_day_from_exe());
-        assert_eq!(part2(&input).unwrap(), 3121910778619);
+        assert_eq!(part2(&input).unwrap().to_string(), "3121910778619");
+    }
+
+    #[test]
+    fn test_pick_digits() {
+        assert_eq!(
+            pick_digits("818181911112111", 12, Pick::Largest).unwrap(),
+            "888911112111"
+        );
+        assert_eq!(
+            pick_digits("818181911112111", 3, Pick::Smallest).unwrap(),
+            "111"
+        );
+        assert_eq!(pick_digits("4213", 2, Pick::Smallest).unwrap(), "13");
+        assert_eq!(pick_digits("4213", 4, Pick::Largest).unwrap(), "4213");
+
+        assert!(pick_digits("12a4", 2, Pick::Largest).is_err());
+        assert!(pick_digits("123", 4, Pick::Largest).is_err());
+    }
+
+    #[test]
+    fn test_large_battery_counts() {
+        let line = "9".repeat(120);
+        let input = format!("{}\n{}\n", line, line);
+
+        // 100 nines doubled is 1 followed by 99 nines and a trailing 8
+        let expected = format!("1{}8", "9".repeat(99));
+        assert_eq!(
+            total_voltage(&input, 100, Pick::Largest)
+                .unwrap()
+                .to_string(),
+            expected
+        );
+
+        let total = total_voltage("00123\n", 4, Pick::Smallest).unwrap();
+        assert_eq!(total.to_string(), "12");
+        assert_eq!(Joltage::default().to_string(), "0");
+    }
+
+    #[test]
+    fn test_explain() {
+        assert_eq!(
+            explain("811111111111119\n", &[2, 12], Pick::Largest).unwrap(),
+            concat!(
+                "Bank 1:\n",
+                "  2 batteries     | 12 batteries\n",
+                "  811111111111119 | 811111111111119\n",
+                "  ^             ^ | ^^^^^^^^^^^   ^\n",
+                "  = 89            | = 811111111119\n",
+                "  at 0,14         | at 0,1,2,3,4,5,6,7,8,9,10,14\n",
+            )
+        );
     }
 }
@@ -1,5 +1,6 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use aoc2025::{extract_day_from_exe, read_input};
+use std::{env, str::FromStr};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Point {
@@ -27,12 +28,187 @@ const NEIGHBORS: [Offset; 8] = [
     (1, 1),
 ];
 
-fn get_grid_state(c: char) -> PosState {
-    match c {
-        '@' => PosState::Paper,
-        '.' => PosState::Empty,
-        'x' => PosState::Pending,
-        _ => panic!("Unknown state: {}", c),
+const VON_NEUMANN_NEIGHBORS: [Offset; 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Neighborhood {
+    /// The 4 orthogonal neighbors.
+    VonNeumann,
+    /// All 8 surrounding cells, the puzzle's forklift.
+    Moore,
+    Custom(Vec<Offset>),
+}
+
+impl Neighborhood {
+    fn offsets(&self) -> &[Offset] {
+        match self {
+            Neighborhood::VonNeumann => &VON_NEUMANN_NEIGHBORS,
+            Neighborhood::Moore => &NEIGHBORS,
+            Neighborhood::Custom(offsets) => offsets,
+        }
+    }
+}
+
+/// `moore`, `von-neumann`, or a custom list of offsets such as `0,-2;0,2`.
+impl FromStr for Neighborhood {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "moore" => Ok(Neighborhood::Moore),
+            "von-neumann" => Ok(Neighborhood::VonNeumann),
+            _ => {
+                let offsets = s
+                    .split(";")
+                    .map(|offset| {
+                        let (x, y) = offset.split_once(",").ok_or_else(|| {
+                            anyhow!("invalid offset, expected <x>,<y>: {}", offset)
+                        })?;
+                        let (x, y) = (x.trim().parse::<i32>()?, y.trim().parse::<i32>()?);
+                        // Offsets are also followed backwards, so they have to be negatable
+                        if x == i32::MIN || y == i32::MIN {
+                            bail!("offset out of range: {}", offset);
+                        }
+                        Ok((x, y))
+                    })
+                    .collect::<Result<Vec<Offset>>>()?;
+                Ok(Neighborhood::Custom(offsets))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl FromStr for Comparison {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lt" => Ok(Comparison::Less),
+            "le" => Ok(Comparison::LessOrEqual),
+            "eq" => Ok(Comparison::Equal),
+            "ge" => Ok(Comparison::GreaterOrEqual),
+            "gt" => Ok(Comparison::Greater),
+            _ => bail!("unknown comparison: {}", s),
+        }
+    }
+}
+
+/// What a neighborhood sees past the edge of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Boundary {
+    /// Nothing, the puzzle's warehouse.
+    Open,
+    /// Walls that count as paper.
+    Walls,
+    /// The opposite edge, as on circular racks.
+    Torus,
+}
+
+impl FromStr for Boundary {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Boundary::Open),
+            "walls" => Ok(Boundary::Walls),
+            "torus" => Ok(Boundary::Torus),
+            _ => bail!("unknown boundary: {}", s),
+        }
+    }
+}
+
+/// A roll is accessible when the number of paper rolls in its neighborhood compares to the
+/// threshold, the puzzle's rule being fewer than 4 of the 8 surrounding cells on an open grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AccessRule {
+    neighborhood: Neighborhood,
+    threshold: usize,
+    comparison: Comparison,
+    boundary: Boundary,
+}
+
+impl Default for AccessRule {
+    fn default() -> Self {
+        AccessRule {
+            neighborhood: Neighborhood::Moore,
+            threshold: 4,
+            comparison: Comparison::Less,
+            boundary: Boundary::Open,
+        }
+    }
+}
+
+impl AccessRule {
+    fn is_accessible(&self, count_paper: usize) -> bool {
+        match self.comparison {
+            Comparison::Less => count_paper < self.threshold,
+            Comparison::LessOrEqual => count_paper <= self.threshold,
+            Comparison::Equal => count_paper == self.threshold,
+            Comparison::GreaterOrEqual => count_paper >= self.threshold,
+            Comparison::Greater => count_paper > self.threshold,
+        }
+    }
+}
+
+/// Characters used for each state in the input grid, `@`, `.` and `x` by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CharMap {
+    paper: char,
+    empty: char,
+    pending: char,
+}
+
+impl Default for CharMap {
+    fn default() -> Self {
+        CharMap {
+            paper: '@',
+            empty: '.',
+            pending: 'x',
+        }
+    }
+}
+
+impl CharMap {
+    fn get_grid_state(&self, c: char) -> Option<PosState> {
+        if c == self.paper {
+            Some(PosState::Paper)
+        } else if c == self.empty {
+            Some(PosState::Empty)
+        } else if c == self.pending {
+            Some(PosState::Pending)
+        } else {
+            None
+        }
+    }
+}
+
+/// Three characters in paper, empty, pending order, e.g. `#.o`.
+impl FromStr for CharMap {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        if chars.len() != 3 || chars[0] == chars[1] || chars[0] == chars[2] || chars[1] == chars[2]
+        {
+            bail!(
+                "expected 3 distinct characters for paper, empty and pending: {:?}",
+                s
+            );
+        }
+        Ok(CharMap {
+            paper: chars[0],
+            empty: chars[1],
+            pending: chars[2],
+        })
     }
 }
 
@@ -42,112 +218,292 @@ fn grid_get(grid: &[Vec<PosState>], p: Point) -> Option<PosState> {
     grid.get(y)?.get(x).copied()
 }
 
-fn has_access(grid: &mut [Vec<PosState>], pos: Point) -> bool {
-    let mut count_paper = 0;
-    for neighbor in NEIGHBORS.iter() {
-        let new_pos = Point {
-            x: pos.x + neighbor.0,
-            y: pos.y + neighbor.1,
-        };
-        if let Some(state) = grid_get(grid, new_pos)
-            && state != PosState::Empty
-        {
-            count_paper += 1;
-        }
-    }
-    if count_paper < 4 {
-        grid[pos.y as usize][pos.x as usize] = PosState::Pending;
-        true
-    } else {
-        false
+/// The cell `offset` away from `pos`, or `None` when that falls off a grid without wrap-around.
+/// The sum is taken in `i64`, so offsets of any `i32` size can't overflow.
+fn neighbor_point(
+    grid: &[Vec<PosState>],
+    pos: Point,
+    offset: Offset,
+    boundary: Boundary,
+) -> Option<Point> {
+    let x = pos.x as i64 + offset.0 as i64;
+    let y = pos.y as i64 + offset.1 as i64;
+
+    if boundary == Boundary::Torus {
+        let height = grid.len() as i64;
+        let width = grid[0].len() as i64;
+        return Some(Point {
+            x: x.rem_euclid(width) as i32,
+            y: y.rem_euclid(height) as i32,
+        });
     }
+    let new_pos = Point {
+        x: i32::try_from(x).ok()?,
+        y: i32::try_from(y).ok()?,
+    };
+    grid_get(grid, new_pos).map(|_| new_pos)
 }
 
-fn clean_grid(grid: &mut [Vec<PosState>]) {
-    let y_size = &grid.len();
-    let x_size = &grid[0].len();
+fn count_paper_neighbors(grid: &[Vec<PosState>], pos: Point, rule: &AccessRule) -> usize {
+    rule.neighborhood
+        .offsets()
+        .iter()
+        .filter(
+            |offset| match neighbor_point(grid, pos, **offset, rule.boundary) {
+                Some(p) => grid[p.y as usize][p.x as usize] != PosState::Empty,
+                None => rule.boundary == Boundary::Walls,
+            },
+        )
+        .count()
+}
 
-    for y in 0..*y_size {
-        for x in 0..*x_size {
-            if grid[y][x] == PosState::Pending {
-                grid[y][x] = PosState::Empty;
+/// Rolls removed in each round (round 1 first), along with the round every cell was cleared in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RemovalHistory {
+    rounds: Vec<Vec<Point>>,
+    round_of: Vec<Vec<Option<usize>>>,
+}
+
+impl RemovalHistory {
+    fn counts_per_round(&self) -> Vec<usize> {
+        self.rounds.iter().map(|round| round.len()).collect()
+    }
+
+    /// The grid as it looks during `round`: rolls removed in that round are marked with `x`, and
+    /// rolls removed in earlier rounds are gone.  Round 0 is the untouched grid.
+    fn render(&self, grid: &[Vec<PosState>], round: usize) -> String {
+        let mut out = String::new();
+
+        for (y, row) in grid.iter().enumerate() {
+            for (x, state) in row.iter().enumerate() {
+                out.push(match (state, self.round_of[y][x]) {
+                    (PosState::Empty, _) => '.',
+                    (_, Some(r)) if r < round => '.',
+                    (_, Some(r)) if r == round => 'x',
+                    (PosState::Pending, _) => 'x',
+                    _ => '@',
+                });
             }
+            out.push('\n');
         }
+
+        out
     }
 }
 
-fn part1(input: &str) -> Result<i64> {
-    let mut total = 0;
-    let mut grid: Vec<Vec<PosState>> = input
-        .lines()
-        .map(|line| line.chars().map(get_grid_state).collect())
-        .collect();
-
-    for y in 0..grid.len() {
-        for x in 0..grid[y].len() {
-            if grid[y][x] == PosState::Paper
-                && has_access(
-                    &mut grid,
-                    Point {
-                        x: x as i32,
-                        y: y as i32,
-                    },
-                )
+/// Remove accessible paper round by round until none is left.  Every roll keeps a count of the
+/// paper in its neighborhood, and after a round only the rolls whose count changed are re-checked
+/// instead of rescanning the whole grid.  Rolls already marked as pending in the input still count
+/// as paper during the first round and are cleared, uncounted, along with it.  If the first round
+/// removes no paper they stay, and nothing is removed at all.
+fn removal_rounds(grid: &[Vec<PosState>], rule: &AccessRule) -> RemovalHistory {
+    let offsets = rule.neighborhood.offsets();
+    let mut rounds: Vec<Vec<Point>> = Vec::new();
+    let mut round_of: Vec<Vec<Option<usize>>> =
+        grid.iter().map(|row| vec![None; row.len()]).collect();
+    let mut counts: Vec<Vec<usize>> = vec![Vec::new(); grid.len()];
+    let mut removed: Vec<Vec<bool>> = vec![Vec::new(); grid.len()];
+    let mut touched: Vec<Vec<bool>> = vec![Vec::new(); grid.len()];
+    let mut current: Vec<Point> = Vec::new();
+
+    for (y, row) in grid.iter().enumerate() {
+        for (x, state) in row.iter().enumerate() {
+            let pos = Point {
+                x: x as i32,
+                y: y as i32,
+            };
+            let count = count_paper_neighbors(grid, pos, rule);
+            if *state == PosState::Pending
+                || (*state == PosState::Paper && rule.is_accessible(count))
             {
-                // dbg!(x, y, total);
-                total += 1;
+                current.push(pos);
             }
+            counts[y].push(count);
+            removed[y].push(*state == PosState::Empty);
+            touched[y].push(false);
         }
     }
-    // dbg!(&grid, total);
 
-    Ok(total)
-}
+    if !current
+        .iter()
+        .any(|pos| grid[pos.y as usize][pos.x as usize] == PosState::Paper)
+    {
+        current.clear();
+    }
 
-fn part2(input: &str) -> Result<i64> {
-    let mut total = 0;
-    let mut grid: Vec<Vec<PosState>> = input
-        .lines()
-        .map(|line| line.chars().map(get_grid_state).collect())
-        .collect();
-
-    let y_size = &grid.len();
-    let x_size = &grid[0].len();
-
-    let mut keep_repeating = true;
-    while keep_repeating {
-        keep_repeating = false;
-        for y in 0..*y_size {
-            for x in 0..*x_size {
-                if grid[y][x] == PosState::Paper
-                    && has_access(
-                        &mut grid,
-                        Point {
-                            x: x as i32,
-                            y: y as i32,
-                        },
-                    )
-                {
-                    // dbg!(x, y, total);
-                    total += 1;
-                    keep_repeating = true;
+    while !current.is_empty() {
+        let mut candidates: Vec<Point> = Vec::new();
+
+        for pos in current.iter() {
+            removed[pos.y as usize][pos.x as usize] = true;
+            round_of[pos.y as usize][pos.x as usize] = Some(rounds.len() + 1);
+        }
+        for pos in current.iter() {
+            // `pos` is in the neighborhood of every cell it can be reached from by an offset
+            for offset in offsets.iter() {
+                let Some(new_pos) =
+                    neighbor_point(grid, *pos, (-offset.0, -offset.1), rule.boundary)
+                else {
+                    continue;
+                };
+                let (x, y) = (new_pos.x as usize, new_pos.y as usize);
+                counts[y][x] -= 1;
+                if !removed[y][x] && !touched[y][x] {
+                    touched[y][x] = true;
+                    candidates.push(new_pos);
                 }
             }
         }
-        if keep_repeating {
-            clean_grid(&mut grid);
+
+        rounds.push(
+            current
+                .into_iter()
+                .filter(|pos| grid[pos.y as usize][pos.x as usize] == PosState::Paper)
+                .collect(),
+        );
+
+        current = Vec::new();
+        for pos in candidates {
+            let (x, y) = (pos.x as usize, pos.y as usize);
+            touched[y][x] = false;
+            if rule.is_accessible(counts[y][x]) {
+                current.push(pos);
+            }
+        }
+    }
+
+    RemovalHistory { rounds, round_of }
+}
+
+/// Parse a rectangular grid, reporting positions 1-based as row and column.
+fn parse_grid(input: &str, chars: &CharMap) -> Result<Vec<Vec<PosState>>> {
+    let mut grid: Vec<Vec<PosState>> = Vec::new();
+
+    for (y, line) in input.lines().enumerate() {
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(x, c)| {
+                chars.get_grid_state(c).ok_or_else(|| {
+                    anyhow!("unknown cell {:?} at row {}, column {}", c, y + 1, x + 1)
+                })
+            })
+            .collect::<Result<Vec<PosState>>>()?;
+
+        if let Some(first) = grid.first()
+            && first.len() != row.len()
+        {
+            bail!(
+                "row {} has {} cells but row 1 has {}",
+                y + 1,
+                row.len(),
+                first.len()
+            );
+        }
+        grid.push(row);
+    }
+
+    if grid.first().is_none_or(|row| row.is_empty()) {
+        bail!("grid is empty");
+    }
+
+    Ok(grid)
+}
+
+fn count_first_round(grid: &[Vec<PosState>], rule: &AccessRule) -> i64 {
+    removal_rounds(grid, rule)
+        .rounds
+        .first()
+        .map_or(0, |round| round.len() as i64)
+}
+
+fn count_all_rounds(grid: &[Vec<PosState>], rule: &AccessRule) -> i64 {
+    removal_rounds(grid, rule)
+        .rounds
+        .iter()
+        .map(|round| round.len() as i64)
+        .sum()
+}
+
+fn part1(input: &str) -> Result<i64> {
+    let grid = parse_grid(input, &CharMap::default())?;
+
+    Ok(count_first_round(&grid, &AccessRule::default()))
+}
+
+fn part2(input: &str) -> Result<i64> {
+    let grid = parse_grid(input, &CharMap::default())?;
+
+    Ok(count_all_rounds(&grid, &AccessRule::default()))
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    rule: Option<AccessRule>,
+    chars: Option<CharMap>,
+    history: bool,
+    render: Option<usize>,
+}
+
+/// Optional custom access rule, e.g. `day04 --neighborhood von-neumann --threshold 2 --compare le`
+/// or `day04 --boundary torus`, and `--chars #.o` for grids drawn with other characters.  Flags
+/// that are left out keep the puzzle's rule.  `--history` lists how many rolls each round removed
+/// and `--render <round>` draws the grid during that round.
+fn parse_args(args: &[String]) -> Result<Options> {
+    let mut options = Options::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "--history" {
+            options.history = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| anyhow!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--neighborhood" => {
+                options.rule.get_or_insert_default().neighborhood = value.parse()?
+            }
+            "--threshold" => options.rule.get_or_insert_default().threshold = value.parse()?,
+            "--compare" => options.rule.get_or_insert_default().comparison = value.parse()?,
+            "--boundary" => options.rule.get_or_insert_default().boundary = value.parse()?,
+            "--chars" => options.chars = Some(value.parse()?),
+            "--render" => options.render = Some(value.parse()?),
+            _ => bail!("unknown argument: {}", arg),
         }
     }
-    // dbg!(&grid, total);
 
-    Ok(total)
+    Ok(options)
 }
 
 fn main() -> Result<()> {
     let day = extract_day_from_exe();
     let input = read_input(day);
+    let options = parse_args(&env::args().skip(1).collect::<Vec<String>>())?;
+
+    if options.history || options.render.is_some() {
+        let grid = parse_grid(&input, &options.chars.unwrap_or_default())?;
+        let history = removal_rounds(&grid, &options.rule.unwrap_or_default());
+        if options.history {
+            for (round, count) in history.counts_per_round().iter().enumerate() {
+                println!("Round {}: {} removed", round + 1, count);
+            }
+        }
+        if let Some(round) = options.render {
+            print!("{}", history.render(&grid, round));
+        }
+        return Ok(());
+    }
 
     println!("Day {:02}", day);
+    if options.rule.is_some() || options.chars.is_some() {
+        let grid = parse_grid(&input, &options.chars.unwrap_or_default())?;
+        let rule = options.rule.unwrap_or_default();
+        println!("===>Part 1: {}", count_first_round(&grid, &rule));
+        println!("===>Part 2: {}", count_all_rounds(&grid, &rule));
+        return Ok(());
+    }
     println!("===>Part 1: {}", part1(&input)?);
     println!("===>Part 2: {}", part2(&input)?);
 
@@ -170,4 +526,211 @@ mod tests {
         let input = read_example(extract_day_from_exe());
         assert_eq!(part2(&input).unwrap(), 43);
     }
+
+    #[test]
+    fn test_access_rules() {
+        let grid = parse_grid("@@@\n@@@\n@@@\n", &CharMap::default()).unwrap();
+
+        // Only the corners have fewer than 3 orthogonal neighbors, then the edges, then the middle
+        let von_neumann = AccessRule {
+            neighborhood: Neighborhood::VonNeumann,
+            threshold: 3,
+            comparison: Comparison::Less,
+            ..Default::default()
+        };
+        let history = removal_rounds(&grid, &von_neumann);
+        assert_eq!(history.counts_per_round(), vec![4, 4, 1]);
+
+        // Only the middle roll has all 8 neighbors
+        let crowded = AccessRule {
+            neighborhood: Neighborhood::Moore,
+            threshold: 8,
+            comparison: Comparison::Equal,
+            ..Default::default()
+        };
+        assert_eq!(count_first_round(&grid, &crowded), 1);
+        assert_eq!(count_all_rounds(&grid, &crowded), 1);
+
+        // A one-sided reach only sees the roll to the right
+        let right_only: Neighborhood = "1,0".parse().unwrap();
+        let rule = AccessRule {
+            neighborhood: right_only,
+            threshold: 1,
+            comparison: Comparison::Less,
+            ..Default::default()
+        };
+        assert_eq!(count_first_round(&grid, &rule), 3);
+        assert_eq!(count_all_rounds(&grid, &rule), 9);
+
+        // Offsets far off the grid are simply never in it, or wrap around on a torus
+        let far: Neighborhood = "2147483647,0;-2147483647,0".parse().unwrap();
+        let rule = AccessRule {
+            neighborhood: far,
+            threshold: 1,
+            ..Default::default()
+        };
+        assert_eq!(count_first_round(&grid, &rule), 9);
+        let torus = AccessRule {
+            boundary: Boundary::Torus,
+            ..rule.clone()
+        };
+        // 2147483647 is 1 more than a multiple of 3, so each roll sees both row neighbors
+        assert_eq!(count_first_round(&grid, &torus), 0);
+        assert!("-2147483648,0".parse::<Neighborhood>().is_err());
+    }
+
+    #[test]
+    fn test_removal_history() {
+        let grid = parse_grid("@@@\n@@@\n@@@\n", &CharMap::default()).unwrap();
+        let rule = AccessRule {
+            neighborhood: Neighborhood::VonNeumann,
+            threshold: 3,
+            comparison: Comparison::Less,
+            ..Default::default()
+        };
+        let history = removal_rounds(&grid, &rule);
+
+        assert_eq!(history.round_of[0], vec![Some(1), Some(2), Some(1)]);
+        assert_eq!(history.round_of[1], vec![Some(2), Some(3), Some(2)]);
+        assert_eq!(history.render(&grid, 0), "@@@\n@@@\n@@@\n");
+        assert_eq!(history.render(&grid, 1), "x@x\n@@@\nx@x\n");
+        assert_eq!(history.render(&grid, 2), ".x.\nx@x\n.x.\n");
+        assert_eq!(history.render(&grid, 4), "...\n...\n...\n");
+    }
+
+    /// The removal from before it was event-driven: every round rescans the whole grid and marks
+    /// accessible rolls as pending, and pending rolls are only cleared after a round that removed
+    /// paper.
+    fn rescan_rounds(grid: &[Vec<PosState>], rule: &AccessRule) -> Vec<usize> {
+        let mut grid = grid.to_vec();
+        let mut counts = Vec::new();
+
+        loop {
+            let mut removed = 0;
+            for y in 0..grid.len() {
+                for x in 0..grid[y].len() {
+                    let pos = Point {
+                        x: x as i32,
+                        y: y as i32,
+                    };
+                    // Rolls marked earlier in this round still count, so the order doesn't matter
+                    if grid[y][x] == PosState::Paper
+                        && rule.is_accessible(count_paper_neighbors(&grid, pos, rule))
+                    {
+                        grid[y][x] = PosState::Pending;
+                        removed += 1;
+                    }
+                }
+            }
+            if removed == 0 {
+                return counts;
+            }
+            for state in grid.iter_mut().flatten() {
+                if *state == PosState::Pending {
+                    *state = PosState::Empty;
+                }
+            }
+            counts.push(removed);
+        }
+    }
+
+    #[test]
+    fn test_removal_matches_rescanning() {
+        let chars = CharMap::default();
+        let pending = parse_grid("x@@@x\n@@@@@\n@@x@@\n@@@@@\n@@@@@\n", &chars).unwrap();
+        let erode = AccessRule {
+            threshold: 5,
+            ..Default::default()
+        };
+        let history = removal_rounds(&pending, &erode);
+        // The `x` cells are cleared uncounted in round 1 alongside the two bottom corners, which
+        // uncovers the rolls around them until all 22 rolls are gone
+        assert_eq!(history.counts_per_round(), vec![2, 8, 8, 4]);
+        assert_eq!(history.round_of[0][0], Some(1));
+        assert!(history.rounds[0].iter().all(|pos| pos.y == 4));
+
+        let example = parse_grid(&read_example(extract_day_from_exe()), &chars).unwrap();
+        let rules = [
+            AccessRule::default(),
+            erode.clone(),
+            AccessRule {
+                neighborhood: Neighborhood::VonNeumann,
+                threshold: 3,
+                ..Default::default()
+            },
+            AccessRule {
+                boundary: Boundary::Torus,
+                threshold: 6,
+                ..Default::default()
+            },
+            AccessRule {
+                boundary: Boundary::Walls,
+                comparison: Comparison::LessOrEqual,
+                ..Default::default()
+            },
+        ];
+        // Pending rolls block the first round, so nothing is ever removed
+        let blocked = parse_grid("xx@xx\nx@@@x\n@@@@@\nx@@@x\nxx@xx\n", &chars).unwrap();
+        let history = removal_rounds(&blocked, &AccessRule::default());
+        assert!(history.rounds.is_empty());
+        assert_eq!(
+            history.render(&blocked, 1),
+            "xx@xx\nx@@@x\n@@@@@\nx@@@x\nxx@xx\n"
+        );
+        assert_eq!(part2("xx@xx\nx@@@x\n@@@@@\nx@@@x\nxx@xx\n").unwrap(), 0);
+
+        for grid in [&pending, &blocked, &example] {
+            for rule in rules.iter() {
+                assert_eq!(
+                    removal_rounds(grid, rule).counts_per_round(),
+                    rescan_rounds(grid, rule)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_grid_errors() {
+        let chars = CharMap::default();
+        let err = parse_grid("@.@\n@?@\n", &chars).unwrap_err();
+        assert_eq!(err.to_string(), "unknown cell '?' at row 2, column 2");
+
+        let err = parse_grid("@.@\n@@\n", &chars).unwrap_err();
+        assert_eq!(err.to_string(), "row 2 has 2 cells but row 1 has 3");
+
+        assert!(parse_grid("", &chars).is_err());
+        assert!(parse_grid("\n", &chars).is_err());
+
+        let custom: CharMap = "#_o".parse().unwrap();
+        assert_eq!(
+            parse_grid("#_o\n", &custom).unwrap(),
+            vec![vec![PosState::Paper, PosState::Empty, PosState::Pending]]
+        );
+        assert!("##.".parse::<CharMap>().is_err());
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let grid = parse_grid("@@@@\n@..@\n@..@\n@@@@\n", &CharMap::default()).unwrap();
+        let mut rule = AccessRule {
+            neighborhood: Neighborhood::VonNeumann,
+            threshold: 3,
+            comparison: Comparison::Less,
+            boundary: Boundary::Open,
+        };
+
+        // Every roll on the ring has 2 orthogonal neighbors, so the ring goes in one round
+        assert_eq!(count_first_round(&grid, &rule), 12);
+
+        // Walls give the corners 4 and the sides 3, so nothing is accessible
+        rule.boundary = Boundary::Walls;
+        assert_eq!(count_all_rounds(&grid, &rule), 0);
+
+        // On a torus every roll sees the opposite edge of the ring as well
+        rule.boundary = Boundary::Torus;
+        assert_eq!(count_all_rounds(&grid, &rule), 0);
+        rule.threshold = 4;
+        assert_eq!(count_first_round(&grid, &rule), 8);
+        assert_eq!(count_all_rounds(&grid, &rule), 12);
+    }
 }
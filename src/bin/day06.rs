@@ -1,101 +1,834 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use aoc2025::{extract_day_from_exe, read_input};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    env, fmt,
+    ops::{Add, Mul, Neg, Sub},
+    rc::Rc,
+    str::FromStr,
+};
 
-fn part1(input: &str) -> Result<i64> {
-    let mut total = 0;
-    let mut grid: Vec<Vec<&str>> = Vec::new();
-
-    for line in input.lines() {
-        grid.push(line.split_whitespace().collect());
-    }
-
-    let math_ops = grid.pop().unwrap();
-    // dbg!(&math_ops);
-
-    for column in 0..grid[0].len() {
-        let mut column_total: i64 = 0;
-        let mut is_first_row = true;
-        for row in grid.iter() {
-            let val = row[column].parse::<i64>().unwrap();
-            if is_first_row {
-                column_total = val;
-                is_first_row = false;
-            } else {
-                match math_ops[column] {
-                    "+" => column_total += val,
-                    "*" => column_total *= val,
-                    _ => panic!("Unknown math operation"),
+/// Arbitrary-precision signed integer with its decimal digits stored least significant first, the
+/// same layout as day03's `Joltage`.  Zero has no digits and is never negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BigInt {
+    negative: bool,
+    digits: Vec<u8>,
+}
+
+fn trim_digits(digits: &mut Vec<u8>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn cmp_digits(a: &[u8], b: &[u8]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut digits = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+
+    for i in 0..a.len().max(b.len()) {
+        let sum = a.get(i).copied().unwrap_or(0) + b.get(i).copied().unwrap_or(0) + carry;
+        digits.push(sum % 10);
+        carry = sum / 10;
+    }
+    if carry > 0 {
+        digits.push(carry);
+    }
+
+    digits
+}
+
+/// `a - b`, where `a` must not be smaller than `b`.
+fn sub_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut digits = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+
+    for (i, digit) in a.iter().enumerate() {
+        let mut diff = *digit as i8 - b.get(i).copied().unwrap_or(0) as i8 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 10;
+            borrow = 1;
+        }
+        digits.push(diff as u8);
+    }
+    trim_digits(&mut digits);
+
+    digits
+}
+
+fn mul_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut columns = vec![0u64; a.len() + b.len()];
+
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            columns[i + j] += (*x as u64) * (*y as u64);
+        }
+    }
+    let mut digits = Vec::with_capacity(columns.len());
+    let mut carry = 0;
+    for column in columns {
+        let sum = column + carry;
+        digits.push((sum % 10) as u8);
+        carry = sum / 10;
+    }
+    trim_digits(&mut digits);
+
+    digits
+}
+
+/// Schoolbook long division, one quotient digit at a time.  `b` must not be zero.
+fn div_digits(a: &[u8], b: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut quotient = vec![0; a.len()];
+    let mut rest: Vec<u8> = Vec::new();
+
+    for (i, digit) in a.iter().enumerate().rev() {
+        rest.insert(0, *digit);
+        trim_digits(&mut rest);
+        while cmp_digits(&rest, b) != Ordering::Less {
+            rest = sub_digits(&rest, b);
+            quotient[i] += 1;
+        }
+    }
+    trim_digits(&mut quotient);
+
+    (quotient, rest)
+}
+
+impl BigInt {
+    fn new(negative: bool, mut digits: Vec<u8>) -> Self {
+        trim_digits(&mut digits);
+        BigInt {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Quotient rounded toward zero and a remainder with the dividend's sign, like Rust's integer
+    /// division.  `None` when dividing by zero.
+    fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, rest) = div_digits(&self.digits, &other.digits);
+
+        Some((
+            BigInt::new(self.negative != other.negative, quotient),
+            BigInt::new(self.negative, rest),
+        ))
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        let sign = if self.negative { -1 } else { 1 };
+        self.digits.iter().rev().try_fold(0i128, |value, digit| {
+            value.checked_mul(10)?.checked_add(sign * *digit as i128)
+        })
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        self.to_i128().and_then(|value| i64::try_from(value).ok())
+    }
+
+    fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0.0, |value, digit| value * 10.0 + *digit as f64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(value: i128) -> Self {
+        let mut magnitude = value.unsigned_abs();
+        let mut digits = Vec::new();
+        while magnitude > 0 {
+            digits.push((magnitude % 10) as u8);
+            magnitude /= 10;
+        }
+
+        BigInt::new(value < 0, digits)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_digits(&self.digits, &other.digits),
+            (true, true) => cmp_digits(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.digits.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_digits(&self.digits, &other.digits));
+        }
+        match cmp_digits(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::new(other.negative, sub_digits(&other.digits, &self.digits)),
+            _ => BigInt::new(self.negative, sub_digits(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            mul_digits(&self.digits, &other.digits),
+        )
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut text: String = self
+            .digits
+            .iter()
+            .rev()
+            .map(|d| (b'0' + d) as char)
+            .collect();
+        if text.is_empty() {
+            text.push('0');
+        } else if self.negative {
+            text.insert(0, '-');
+        }
+
+        f.pad(&text)
+    }
+}
+
+type OpFn = Rc<dyn Fn(&BigInt, &BigInt) -> Result<BigInt>>;
+
+struct Operator {
+    name: String,
+    apply: OpFn,
+}
+
+/// Operators by the symbol used in the worksheet's last row.  Every operator folds a problem's
+/// numbers from left to right on exact integers, and `Arithmetic` decides how large they may get.
+struct OperatorTable {
+    ops: HashMap<char, Operator>,
+}
+
+impl OperatorTable {
+    fn new() -> Self {
+        let mut table = OperatorTable {
+            ops: HashMap::new(),
+        };
+
+        table.register('+', "add", |a, b| Ok(a + b));
+        table.register('*', "mul", |a, b| Ok(a * b));
+        table.register('-', "sub", |a, b| Ok(a - b));
+        table.register('/', "div", |a, b| {
+            a.div_rem(b)
+                .map(|(quotient, _)| quotient)
+                .ok_or_else(|| anyhow!("division of {} by zero", a))
+        });
+        table.register('<', "min", |a, b| Ok(a.min(b).clone()));
+        table.register('>', "max", |a, b| Ok(a.max(b).clone()));
+
+        table
+    }
+
+    fn register(
+        &mut self,
+        symbol: char,
+        name: &str,
+        apply: impl Fn(&BigInt, &BigInt) -> Result<BigInt> + 'static,
+    ) {
+        self.ops.insert(
+            symbol,
+            Operator {
+                name: name.to_string(),
+                apply: Rc::new(apply),
+            },
+        );
+    }
+
+    /// Register `symbol` as another name for an existing operator, e.g. `x` for `mul`.
+    fn register_alias(&mut self, symbol: char, name: &str) -> Result<()> {
+        let apply = self
+            .ops
+            .values()
+            .find(|op| op.name == name)
+            .map(|op| Rc::clone(&op.apply))
+            .ok_or_else(|| anyhow!("unknown operator name: {}", name))?;
+
+        self.ops.insert(
+            symbol,
+            Operator {
+                name: name.to_string(),
+                apply,
+            },
+        );
+        Ok(())
+    }
+
+    fn fold(&self, symbol: char, operands: &[BigInt], arithmetic: Arithmetic) -> Result<BigInt> {
+        let op = self
+            .ops
+            .get(&symbol)
+            .ok_or_else(|| anyhow!("unknown math operation {:?}", symbol))?;
+        let (first, rest) = operands
+            .split_first()
+            .ok_or_else(|| anyhow!("no numbers to {}", op.name))?;
+        let mut column_total = first.clone();
+
+        for val in rest {
+            column_total = (op.apply)(&column_total, val)?;
+            arithmetic.check(&column_total)?;
+        }
+
+        Ok(column_total)
+    }
+}
+
+/// `Checked` keeps every intermediate result within `i64` like the puzzle expects and `Int128`
+/// within `i128`, reporting an overflow past them.  `Big` never overflows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arithmetic {
+    Checked,
+    Int128,
+    Big,
+}
+
+impl Arithmetic {
+    fn check(self, value: &BigInt) -> Result<()> {
+        match self {
+            Arithmetic::Checked if value.to_i64().is_none() => bail!("{} overflows i64", value),
+            Arithmetic::Int128 if value.to_i128().is_none() => bail!("{} overflows i128", value),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for Arithmetic {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "checked" => Ok(Arithmetic::Checked),
+            "i128" => Ok(Arithmetic::Int128),
+            "big" => Ok(Arithmetic::Big),
+            _ => bail!("unknown arithmetic: {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reading {
+    /// Part 1, one number per row.
+    Rows,
+    /// Part 2, one number per column.
+    Columns,
+}
+
+/// One problem on the worksheet: the columns it spans, its operator and the text of each number
+/// row within those columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Problem {
+    start: usize,
+    end: usize,
+    op: char,
+    cells: Vec<Vec<char>>,
+}
+
+impl Problem {
+    /// Part 1 reading, one number per row.
+    fn row_operands(&self) -> Result<Vec<i64>> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(y, cell)| {
+                let text: String = cell.iter().collect();
+                text.trim().parse::<i64>().map_err(|_| {
+                    anyhow!(
+                        "invalid number {:?} in row {}, columns {}-{}",
+                        text.trim(),
+                        y + 1,
+                        self.start + 1,
+                        self.end
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Part 2 reading, one number per column with the digits running top to bottom and the columns
+    /// read right to left as the puzzle describes.  With only `+` and `*` the order never showed,
+    /// but `-` and `/` depend on it.
+    fn column_operands(&self) -> Result<Vec<i64>> {
+        (0..self.end - self.start)
+            .rev()
+            .map(|x| {
+                // Shorter numbers leave blanks anywhere in a column, not just at its ends
+                let text: String = self
+                    .cells
+                    .iter()
+                    .map(|cell| cell[x])
+                    .filter(|c| *c != ' ')
+                    .collect();
+                text.parse::<i64>().map_err(|_| {
+                    anyhow!("invalid number {:?} in column {}", text, self.start + x + 1)
+                })
+            })
+            .collect()
+    }
+
+    fn operands(&self, reading: Reading) -> Result<Vec<i64>> {
+        match reading {
+            Reading::Rows => self.row_operands(),
+            Reading::Columns => self.column_operands(),
+        }
+    }
+
+    fn evaluate(
+        &self,
+        reading: Reading,
+        table: &OperatorTable,
+        arithmetic: Arithmetic,
+    ) -> Result<BigInt> {
+        let operands: Vec<BigInt> = self
+            .operands(reading)?
+            .into_iter()
+            .map(|n| BigInt::from(n as i128))
+            .collect();
+
+        table.fold(self.op, &operands, arithmetic)
+    }
+}
+
+/// The number rows followed by the operator row.  Rows are padded with spaces to the longest row,
+/// problems are the runs of columns between all-blank columns, and each operator belongs to the
+/// problem whose columns it sits in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Worksheet {
+    problems: Vec<Problem>,
+}
+
+impl FromStr for Worksheet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows: Vec<Vec<char>> = s
+            .lines()
+            .map(|line| line.trim_end_matches('\r').chars().collect())
+            .collect();
+        while rows.last().is_some_and(|row| row.iter().all(|c| *c == ' ')) {
+            rows.pop();
+        }
+        let op_row = rows.pop().ok_or_else(|| anyhow!("worksheet is empty"))?;
+        if rows.is_empty() {
+            bail!("worksheet has an operator row but no numbers");
+        }
+
+        let width = rows
+            .iter()
+            .chain([&op_row])
+            .map(|row| row.len())
+            .max()
+            .unwrap();
+        for row in rows.iter_mut() {
+            row.resize(width, ' ');
+        }
+
+        let is_blank = |x: usize| rows.iter().all(|row| row[x] == ' ');
+        let mut problems: Vec<Problem> = Vec::new();
+        let mut x = 0;
+        while x < width {
+            if is_blank(x) {
+                if let Some(op) = op_row.get(x).filter(|c| **c != ' ') {
+                    bail!(
+                        "operator {:?} at column {} is not under any problem",
+                        op,
+                        x + 1
+                    );
                 }
+                x += 1;
+                continue;
             }
+
+            let start = x;
+            while x < width && !is_blank(x) {
+                x += 1;
+            }
+            let ops: Vec<char> = op_row
+                .iter()
+                .take(x)
+                .skip(start)
+                .filter(|c| **c != ' ')
+                .copied()
+                .collect();
+            let op = match ops[..] {
+                [op] => op,
+                [] => bail!("problem at columns {}-{} has no operator", start + 1, x),
+                _ => bail!(
+                    "problem at columns {}-{} has {} operators",
+                    start + 1,
+                    x,
+                    ops.len()
+                ),
+            };
+
+            problems.push(Problem {
+                start,
+                end: x,
+                op,
+                cells: rows.iter().map(|row| row[start..x].to_vec()).collect(),
+            });
         }
-        total += column_total;
+
+        Ok(Worksheet { problems })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProblemResult {
+    start: usize,
+    end: usize,
+    result: BigInt,
+}
+
+/// Evaluate every problem, naming the problem's columns when one of them fails or overflows.
+fn problem_results(
+    worksheet: &Worksheet,
+    reading: Reading,
+    table: &OperatorTable,
+    arithmetic: Arithmetic,
+) -> Result<Vec<ProblemResult>> {
+    worksheet
+        .problems
+        .iter()
+        .map(|problem| {
+            let result = problem.evaluate(reading, table, arithmetic).map_err(|e| {
+                anyhow!(
+                    "problem at columns {}-{}: {}",
+                    problem.start + 1,
+                    problem.end,
+                    e
+                )
+            })?;
+            Ok(ProblemResult {
+                start: problem.start,
+                end: problem.end,
+                result,
+            })
+        })
+        .collect()
+}
+
+fn grand_total(results: &[ProblemResult], arithmetic: Arithmetic) -> Result<BigInt> {
+    let mut total = BigInt::from(0);
+
+    for problem in results {
+        total = &total + &problem.result;
+        arithmetic.check(&total).map_err(|e| {
+            anyhow!(
+                "grand total overflows at problem at columns {}-{}: {}",
+                problem.start + 1,
+                problem.end,
+                e
+            )
+        })?;
     }
 
     Ok(total)
 }
 
-fn part2(input: &str) -> Result<i64> {
-    let mut total = 0;
-    let mut grid: Vec<Vec<char>> = Vec::new();
+fn solve(
+    worksheet: &Worksheet,
+    reading: Reading,
+    table: &OperatorTable,
+    arithmetic: Arithmetic,
+) -> Result<BigInt> {
+    grand_total(
+        &problem_results(worksheet, reading, table, arithmetic)?,
+        arithmetic,
+    )
+}
+
+/// Problems ordered from the largest magnitude down, with each one's share of the grand total.
+fn format_results(results: &[ProblemResult], total: &BigInt) -> String {
+    let mut sorted = results.to_vec();
+    sorted.sort_by(|a, b| cmp_digits(&b.result.digits, &a.result.digits));
 
-    for line in input.lines() {
-        grid.push(line.chars().collect());
+    let mut out = String::new();
+    for problem in sorted {
+        let share = if total.is_zero() {
+            0.0
+        } else {
+            problem.result.to_f64() / total.to_f64() * 100.0
+        };
+        out += &format!(
+            "  columns {:>5}-{:<5} {:>24} {:>7.2}%\n",
+            problem.start + 1,
+            problem.end,
+            problem.result,
+            share
+        );
     }
 
-    let mut math_ops = Vec::new();
-    let last_row = grid.pop().unwrap();
-    for c in last_row.iter() {
-        if *c != ' ' {
-            math_ops.push(c);
+    out
+}
+
+fn part1(input: &str) -> Result<i64> {
+    let worksheet: Worksheet = input.parse()?;
+    let total = solve(
+        &worksheet,
+        Reading::Rows,
+        &OperatorTable::new(),
+        Arithmetic::Checked,
+    )?;
+
+    total
+        .to_i64()
+        .ok_or_else(|| anyhow!("{} overflows i64", total))
+}
+
+fn part2(input: &str) -> Result<i64> {
+    let worksheet: Worksheet = input.parse()?;
+    let total = solve(
+        &worksheet,
+        Reading::Columns,
+        &OperatorTable::new(),
+        Arithmetic::Checked,
+    )?;
+
+    total
+        .to_i64()
+        .ok_or_else(|| anyhow!("{} overflows i64", total))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BreakdownFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for BreakdownFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(BreakdownFormat::Text),
+            "json" => Ok(BreakdownFormat::Json),
+            _ => bail!("unknown breakdown format: {}", s),
         }
     }
+}
+
+/// Both readings of a single problem, for comparing part 1 and part 2 side by side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProblemBreakdown {
+    start: usize,
+    end: usize,
+    op: char,
+    row_operands: Vec<i64>,
+    row_result: BigInt,
+    column_operands: Vec<i64>,
+    column_result: BigInt,
+}
+
+fn breakdown(
+    worksheet: &Worksheet,
+    table: &OperatorTable,
+    arithmetic: Arithmetic,
+) -> Result<Vec<ProblemBreakdown>> {
+    let rows = problem_results(worksheet, Reading::Rows, table, arithmetic)?;
+    let columns = problem_results(worksheet, Reading::Columns, table, arithmetic)?;
 
-    let mut num_grid: Vec<Vec<i64>> = Vec::new();
-    let mut num_column: Vec<i64> = Vec::new();
-    for x in 0..grid[0].len() {
-        let mut num_str: String = String::new();
-        for row in grid.iter() {
-            if row[x] != ' ' {
-                num_str += &row[x].to_string();
+    worksheet
+        .problems
+        .iter()
+        .zip(rows.iter().zip(columns.iter()))
+        .map(|(problem, (row, column))| {
+            Ok(ProblemBreakdown {
+                start: problem.start,
+                end: problem.end,
+                op: problem.op,
+                row_operands: problem.row_operands()?,
+                row_result: row.result.clone(),
+                column_operands: problem.column_operands()?,
+                column_result: column.result.clone(),
+            })
+        })
+        .collect()
+}
+
+fn join_operands(operands: &[i64], separator: &str) -> String {
+    operands
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+fn format_breakdown(problems: &[ProblemBreakdown], format: BreakdownFormat) -> String {
+    let mut out = String::new();
+
+    match format {
+        BreakdownFormat::Text => {
+            for problem in problems {
+                let op = format!(" {} ", problem.op);
+                out += &format!(
+                    "columns {}-{} ({})\n  rows:    {} = {}\n  columns: {} = {}\n",
+                    problem.start + 1,
+                    problem.end,
+                    problem.op,
+                    join_operands(&problem.row_operands, &op),
+                    problem.row_result,
+                    join_operands(&problem.column_operands, &op),
+                    problem.column_result
+                );
             }
         }
-        if num_str.is_empty() {
-            num_grid.push(num_column);
-            num_column = Vec::new();
-        } else {
-            num_column.push(num_str.parse::<i64>().unwrap());
-        }
-    }
-    num_grid.push(num_column);
-
-    for (math_idx, x) in num_grid.iter().enumerate() {
-        let mut column_total: i64 = 0;
-        let mut is_first_row = true;
-        for y in x.iter() {
-            let val = y;
-            if is_first_row {
-                column_total = *val;
-                is_first_row = false;
-            } else {
-                // println!("math={} on val={}", math_ops[math_idx], val);
-                match math_ops[math_idx] {
-                    '+' => column_total += val,
-                    '*' => column_total *= val,
-                    _ => panic!("Unknown math operation"),
-                }
+        BreakdownFormat::Json => {
+            let entries: Vec<String> = problems
+                .iter()
+                .map(|problem| {
+                    format!(
+                        concat!(
+                            "{{\"start\":{},\"end\":{},\"op\":{:?},",
+                            "\"rows\":{{\"operands\":[{}],\"result\":{}}},",
+                            "\"columns\":{{\"operands\":[{}],\"result\":{}}}}}"
+                        ),
+                        problem.start + 1,
+                        problem.end,
+                        problem.op.to_string(),
+                        join_operands(&problem.row_operands, ","),
+                        problem.row_result,
+                        join_operands(&problem.column_operands, ","),
+                        problem.column_result
+                    )
+                })
+                .collect();
+            out += &format!("[{}]\n", entries.join(","));
+        }
+    }
+
+    out
+}
+
+#[derive(Default)]
+struct Options {
+    table: Option<OperatorTable>,
+    arithmetic: Option<Arithmetic>,
+    results: bool,
+    breakdown: Option<BreakdownFormat>,
+}
+
+/// Optional operator aliases and arithmetic, e.g. `day06 --op x=mul --op m=min --arithmetic big`,
+/// `--results` to list every problem's result from the largest down, and `--breakdown text|json`
+/// to show each problem's numbers under both readings.
+fn parse_args(args: &[String]) -> Result<Options> {
+    let mut options = Options::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "--results" {
+            options.results = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| anyhow!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--op" => {
+                let (symbol, name) = value
+                    .split_once("=")
+                    .ok_or_else(|| anyhow!("expected <symbol>=<operator name>: {}", value))?;
+                let mut chars = symbol.chars();
+                let (Some(c), None) = (chars.next(), chars.next()) else {
+                    bail!("operator symbol must be a single character: {:?}", symbol);
+                };
+                options
+                    .table
+                    .get_or_insert_with(OperatorTable::new)
+                    .register_alias(c, name)?;
             }
+            "--arithmetic" => options.arithmetic = Some(value.parse()?),
+            "--breakdown" => options.breakdown = Some(value.parse()?),
+            _ => bail!("unknown argument: {}", arg),
         }
-        total += column_total;
     }
 
-    Ok(total)
+    Ok(options)
 }
 
 fn main() -> Result<()> {
     let day = extract_day_from_exe();
     let input = read_input(day);
+    let options = parse_args(&env::args().skip(1).collect::<Vec<String>>())?;
+
+    if let Some(format) = options.breakdown {
+        let worksheet: Worksheet = input.parse()?;
+        let table = options.table.unwrap_or_else(OperatorTable::new);
+        let arithmetic = options.arithmetic.unwrap_or(Arithmetic::Checked);
+        print!(
+            "{}",
+            format_breakdown(&breakdown(&worksheet, &table, arithmetic)?, format)
+        );
+        return Ok(());
+    }
 
     println!("Day {:02}", day);
+    if options.table.is_some() || options.arithmetic.is_some() || options.results {
+        let worksheet: Worksheet = input.parse()?;
+        let table = options.table.unwrap_or_else(OperatorTable::new);
+        let arithmetic = options.arithmetic.unwrap_or(Arithmetic::Checked);
+
+        for (part, reading) in [(1, Reading::Rows), (2, Reading::Columns)] {
+            let results = problem_results(&worksheet, reading, &table, arithmetic)?;
+            let total = grand_total(&results, arithmetic)?;
+            println!("===>Part {}: {}", part, total);
+            if options.results {
+                print!("{}", format_results(&results, &total));
+            }
+        }
+        return Ok(());
+    }
     println!("===>Part 1: {}", part1(&input)?);
     println!("===>Part 2: {}", part2(&input)?);
 
@@ -118,4 +851,198 @@ mod tests {
         let input = read_example(extract_day_from_exe());
         assert_eq!(part2(&input).unwrap(), 3263827);
     }
+
+    #[test]
+    fn test_ragged_rows() {
+        // Trailing spaces stripped from every row but the first
+        let input = "123 328  51 64 \n 45 64  387 23\n  6 98  215 314\n*   +   *   +\n";
+        assert_eq!(part1(input).unwrap(), 4277556);
+        assert_eq!(part2(input).unwrap(), 3263827);
+
+        // A short number in a middle row leaves a blank inside a column: 346 * 25 * 1
+        assert_eq!(part2("123\n  4\n 56\n*\n").unwrap(), 8650);
+    }
+
+    #[test]
+    fn test_malformed_worksheets() {
+        let err = "12 34\n5  6\n*\n".parse::<Worksheet>().unwrap_err();
+        assert_eq!(err.to_string(), "problem at columns 4-5 has no operator");
+
+        let err = "12 34\n5  6\n*+ +\n".parse::<Worksheet>().unwrap_err();
+        assert_eq!(err.to_string(), "problem at columns 1-2 has 2 operators");
+
+        let err = "12  34\n5   6\n*  ++\n".parse::<Worksheet>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "operator '+' at column 4 is not under any problem"
+        );
+
+        assert!("\n\n".parse::<Worksheet>().is_err());
+        assert!("+ *\n".parse::<Worksheet>().is_err());
+        assert!(part1("1x 2\n3  4\n+  *\n").is_err());
+    }
+
+    fn big(values: &[i128]) -> Vec<BigInt> {
+        values.iter().map(|n| BigInt::from(*n)).collect()
+    }
+
+    #[test]
+    fn test_operator_table() {
+        let mut table = OperatorTable::new();
+        let checked = Arithmetic::Checked;
+        let fold = |table: &OperatorTable, op: char, values: &[i128], arithmetic: Arithmetic| {
+            table
+                .fold(op, &big(values), arithmetic)
+                .map(|n| n.to_string())
+        };
+        assert_eq!(fold(&table, '-', &[100, 30, 20], checked).unwrap(), "50");
+        assert_eq!(fold(&table, '/', &[100, 5, 3], checked).unwrap(), "6");
+        assert_eq!(fold(&table, '<', &[7, 3, 9], checked).unwrap(), "3");
+        assert_eq!(fold(&table, '>', &[7, 3, 9], checked).unwrap(), "9");
+        assert_eq!(
+            fold(&table, '/', &[1, 0], checked).unwrap_err().to_string(),
+            "division of 1 by zero"
+        );
+        assert!(fold(&table, '%', &[1, 2], checked).is_err());
+
+        table.register('%', "mod", |a, b| {
+            a.div_rem(b)
+                .map(|(_, rest)| rest)
+                .ok_or_else(|| anyhow!("{} mod {}", a, b))
+        });
+        table.register_alias('x', "mul").unwrap();
+        assert_eq!(fold(&table, '%', &[17, 5], checked).unwrap(), "2");
+        assert_eq!(fold(&table, 'x', &[6, 7], checked).unwrap(), "42");
+
+        let wide = i64::MAX as i128;
+        assert!(fold(&table, '*', &[wide, 2], checked).is_err());
+        assert_eq!(
+            fold(&table, '*', &[wide, 2], Arithmetic::Int128).unwrap(),
+            (wide * 2).to_string()
+        );
+        assert_eq!(
+            fold(&table, '*', &[i128::MAX, 2], Arithmetic::Int128)
+                .unwrap_err()
+                .to_string(),
+            "340282366920938463463374607431768211454 overflows i128"
+        );
+        assert_eq!(
+            fold(&table, '*', &[i128::MAX, 2], Arithmetic::Big).unwrap(),
+            "340282366920938463463374607431768211454"
+        );
+    }
+
+    #[test]
+    fn test_big_int() {
+        let a = BigInt::from(-1234567);
+        let b = BigInt::from(890);
+        assert_eq!((&a + &b).to_string(), "-1233677");
+        assert_eq!((&b - &a).to_string(), "1235457");
+        assert_eq!((&b - &b).to_string(), "0");
+        assert_eq!((&a * &b).to_string(), "-1098764630");
+        let (quotient, rest) = a.div_rem(&b).unwrap();
+        assert_eq!(
+            (quotient.to_string(), rest.to_string()),
+            ("-1387".into(), "-137".into())
+        );
+        assert!(a.div_rem(&BigInt::from(0)).is_none());
+        assert!(a < b && BigInt::from(-2) < BigInt::from(-1));
+        assert_eq!(BigInt::from(i128::MIN).to_i128(), Some(i128::MIN));
+        assert_eq!(format!("{:>6}", BigInt::from(-42)), "   -42");
+
+        // 9999999999 to the fifth is far past i128
+        let worksheet: Worksheet = format!("{}*\n", "9999999999\n".repeat(5)).parse().unwrap();
+        let table = OperatorTable::new();
+        assert!(solve(&worksheet, Reading::Rows, &table, Arithmetic::Int128).is_err());
+        assert_eq!(
+            solve(&worksheet, Reading::Rows, &table, Arithmetic::Big)
+                .unwrap()
+                .to_string(),
+            "99999999950000000009999999999000000000049999999999"
+        );
+    }
+
+    #[test]
+    fn test_worksheet_with_new_operators() {
+        let input = "20 9 4\n 5 3 7\n-  / >\n";
+        // 20 - 5, 9 / 3 and max(4, 7)
+        assert_eq!(part1(input).unwrap(), 25);
+        // 5 - 2 reading the columns right to left, 93 and 47
+        assert_eq!(part2(input).unwrap(), 143);
+    }
+
+    #[test]
+    fn test_overflow_names_problem() {
+        let input = "9999999999 1\n9999999999 2\n9999999999 3\n*          +\n";
+        let err = part1(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "problem at columns 1-10: 99999999980000000001 overflows i64"
+        );
+
+        let worksheet: Worksheet = input.parse().unwrap();
+        let table = OperatorTable::new();
+        let int128 = solve(&worksheet, Reading::Rows, &table, Arithmetic::Int128).unwrap();
+        assert_eq!(int128, BigInt::from(9999999999i128.pow(3) + 6));
+
+        let err = part1("9223372036854775807 1\n+                   +\n").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("grand total overflows at problem at columns 21-21")
+        );
+    }
+
+    #[test]
+    fn test_format_results() {
+        let results = [
+            ProblemResult {
+                start: 0,
+                end: 3,
+                result: BigInt::from(25),
+            },
+            ProblemResult {
+                start: 4,
+                end: 6,
+                result: BigInt::from(75),
+            },
+        ];
+        assert_eq!(
+            format_results(&results, &BigInt::from(100)),
+            concat!(
+                "  columns     5-6                           75   75.00%\n",
+                "  columns     1-3                           25   25.00%\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let worksheet: Worksheet = "123 51\n 45 387\n*   +\n".parse().unwrap();
+        let problems = breakdown(&worksheet, &OperatorTable::new(), Arithmetic::Checked).unwrap();
+
+        assert_eq!(
+            problems[0],
+            ProblemBreakdown {
+                start: 0,
+                end: 3,
+                op: '*',
+                row_operands: vec![123, 45],
+                row_result: BigInt::from(5535),
+                column_operands: vec![35, 24, 1],
+                column_result: BigInt::from(840),
+            }
+        );
+        assert_eq!(
+            format_breakdown(&problems[1..], BreakdownFormat::Text),
+            "columns 5-7 (+)\n  rows:    51 + 387 = 438\n  columns: 7 + 18 + 53 = 78\n"
+        );
+        assert_eq!(
+            format_breakdown(&problems[1..], BreakdownFormat::Json),
+            concat!(
+                "[{\"start\":5,\"end\":7,\"op\":\"+\",",
+                "\"rows\":{\"operands\":[51,387],\"result\":438},",
+                "\"columns\":{\"operands\":[7,18,53],\"result\":78}}]\n"
+            )
+        );
+    }
 }
@@ -1,7 +1,10 @@
-use std::collections::HashMap;
-
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use aoc2025::{extract_day_from_exe, read_input};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    str::FromStr,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ManifoldState {
@@ -9,6 +12,33 @@ enum ManifoldState {
     Start,
     Beam,
     Splitter,
+    /// `/`, turns a beam travelling down to the left and one travelling right upwards.
+    MirrorSlash,
+    /// `\`, turns a beam travelling down to the right and one travelling right downwards.
+    MirrorBackslash,
+    /// `<`, a splitter that only puts beams arriving from above or below on its left.
+    DeflectorLeft,
+    /// `>`, a splitter that only puts beams arriving from above or below on its right.
+    DeflectorRight,
+    /// `#`, stops any beam.
+    Absorber,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn perpendicular(self) -> [Direction; 2] {
+        match self {
+            Direction::Up | Direction::Down => [Direction::Left, Direction::Right],
+            Direction::Left | Direction::Right => [Direction::Up, Direction::Down],
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -17,137 +47,652 @@ struct Point {
     y: usize,
 }
 
-fn get_manifold_state(c: char) -> ManifoldState {
-    match c {
+fn get_manifold_state(c: char) -> Option<ManifoldState> {
+    Some(match c {
         '.' => ManifoldState::Empty,
         'S' => ManifoldState::Start,
         '|' => ManifoldState::Beam,
         '^' => ManifoldState::Splitter,
-        _ => panic!("Unknown manifold state: {}", c),
-    }
+        '/' => ManifoldState::MirrorSlash,
+        '\\' => ManifoldState::MirrorBackslash,
+        '<' => ManifoldState::DeflectorLeft,
+        '>' => ManifoldState::DeflectorRight,
+        '#' => ManifoldState::Absorber,
+        _ => return None,
+    })
 }
 
-fn get_starting_point(grid: &[Vec<ManifoldState>]) -> Point {
+fn get_starting_point(grid: &[Vec<ManifoldState>]) -> Result<Point> {
     for (y, row) in grid.iter().enumerate() {
         for (x, &value) in row.iter().enumerate() {
             if value == ManifoldState::Start {
-                return Point { x, y };
+                return Ok(Point { x, y });
             }
         }
     }
-    panic!("No where to start from")
+    bail!("no starting point 'S' in the manifold")
+}
+
+/// Result of sweeping the beam down the manifold one row at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BeamSweep {
+    /// Number of splitters reached by at least one beam.
+    splits: i64,
+    /// Number of timelines ending in each column of the last row.
+    timelines: Vec<i64>,
+}
+
+/// Carry the number of timelines in every column from the start row to the bottom.  A splitter
+/// sends every timeline arriving on it to both neighboring columns, anything else lets them carry
+/// straight on.  Beams are merged per column, so this is a single O(W·H) pass with no recursion.
+///
+/// Only splitters change a beam; `|` and `S` cells below the start are passed straight through.
+/// A start on the last row never meets a splitter and leaves a single timeline in its column, and
+/// a splitter on the left or right border loses the beam that would leave the manifold.
+fn sweep(manifold: &[Vec<ManifoldState>]) -> Result<BeamSweep> {
+    let starting_point = get_starting_point(manifold)?;
+    let x_size = manifold[0].len();
+    let mut splits = 0;
+    let mut timelines: Vec<i64> = vec![0; x_size];
+    timelines[starting_point.x] = 1;
+
+    for row in manifold.iter().skip(starting_point.y + 1) {
+        let mut next: Vec<i64> = vec![0; x_size];
+        for (x, count) in timelines.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            if row[x] == ManifoldState::Splitter {
+                splits += 1;
+                if x > 0 {
+                    next[x - 1] += count;
+                }
+                if x + 1 < x_size {
+                    next[x + 1] += count;
+                }
+            } else {
+                next[x] += count;
+            }
+        }
+        timelines = next;
+    }
+
+    Ok(BeamSweep { splits, timelines })
+}
+
+/// Parse the manifold, which has to be a non-empty rectangle with at least one `S`.
+fn parse_manifold(input: &str) -> Result<Vec<Vec<ManifoldState>>> {
+    let mut manifold: Vec<Vec<ManifoldState>> = Vec::new();
+
+    for (y, line) in input.lines().enumerate() {
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(x, c)| {
+                get_manifold_state(c).ok_or_else(|| {
+                    anyhow!("line {}, column {}: unknown cell '{}'", y + 1, x + 1, c)
+                })
+            })
+            .collect::<Result<Vec<ManifoldState>>>()?;
+        if let Some(first) = manifold.first()
+            && first.len() != row.len()
+        {
+            bail!(
+                "line {}: expected {} cells but found {}",
+                y + 1,
+                first.len(),
+                row.len()
+            );
+        }
+        manifold.push(row);
+    }
+
+    if manifold.first().is_none_or(|row| row.is_empty()) {
+        bail!("the manifold is empty");
+    }
+    get_starting_point(&manifold)?;
+
+    Ok(manifold)
+}
+
+fn move_point(manifold: &[Vec<ManifoldState>], p: Point, dir: Direction) -> Option<Point> {
+    let (x, y) = match dir {
+        Direction::Up => (p.x, p.y.checked_sub(1)?),
+        Direction::Down => (p.x, p.y + 1),
+        Direction::Left => (p.x.checked_sub(1)?, p.y),
+        Direction::Right => (p.x + 1, p.y),
+    };
+    manifold.get(y)?.get(x).map(|_| Point { x, y })
+}
+
+/// A beam sitting on `pos` and about to move one cell in `dir`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct BeamState {
+    pos: Point,
+    dir: Direction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Beam(BeamState),
+    Exit,
+    Absorbed,
+}
+
+/// What a beam travelling `dir` does once it is on `pos`: carry on, turn, or stop there.  A
+/// splitter or deflector hands the beam to the cells beside it instead, see `step`.
+fn redirect(manifold: &[Vec<ManifoldState>], pos: Point, dir: Direction) -> Vec<Step> {
+    let beam = |dir: Direction| vec![Step::Beam(BeamState { pos, dir })];
+    let beside = |sides: &[Direction]| -> Vec<Step> {
+        sides
+            .iter()
+            .filter_map(|side| move_point(manifold, pos, *side))
+            .flat_map(|side| match manifold[side.y][side.x] {
+                // A beam placed on another splitter stays there, like the row-by-row sweep
+                ManifoldState::Splitter
+                | ManifoldState::DeflectorLeft
+                | ManifoldState::DeflectorRight => {
+                    vec![Step::Beam(BeamState { pos: side, dir })]
+                }
+                _ => redirect(manifold, side, dir),
+            })
+            .collect()
+    };
+    let vertical = matches!(dir, Direction::Up | Direction::Down);
+
+    match manifold[pos.y][pos.x] {
+        ManifoldState::Empty | ManifoldState::Start | ManifoldState::Beam => beam(dir),
+        ManifoldState::Splitter => beside(&dir.perpendicular()),
+        ManifoldState::DeflectorLeft if vertical => beside(&[Direction::Left]),
+        ManifoldState::DeflectorRight if vertical => beside(&[Direction::Right]),
+        ManifoldState::DeflectorLeft | ManifoldState::DeflectorRight => beam(dir),
+        ManifoldState::MirrorSlash => beam(match dir {
+            Direction::Up => Direction::Right,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Right => Direction::Up,
+        }),
+        ManifoldState::MirrorBackslash => beam(match dir {
+            Direction::Up => Direction::Left,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Up,
+            Direction::Right => Direction::Down,
+        }),
+        ManifoldState::Absorber => vec![Step::Absorbed],
+    }
+}
+
+/// Move a beam one cell.  A splitter puts the beam on the cells beside it, still travelling the
+/// same way, just like the downward-only puzzle does, and a deflector does the same on its one
+/// side for beams arriving from above or below.  Whatever is beside them still acts on the beam,
+/// and beams that would land outside the manifold are lost rather than counted as exits.
+fn step(manifold: &[Vec<ManifoldState>], beam: BeamState) -> Vec<Step> {
+    match move_point(manifold, beam.pos, beam.dir) {
+        Some(next) => redirect(manifold, next, beam.dir),
+        None => vec![Step::Exit],
+    }
 }
 
-fn part1(input: &str) -> Result<i32> {
+fn get_sources(manifold: &[Vec<ManifoldState>]) -> Vec<BeamState> {
+    let mut sources: Vec<BeamState> = Vec::new();
+
+    for (y, row) in manifold.iter().enumerate() {
+        for (x, &value) in row.iter().enumerate() {
+            if value == ManifoldState::Start {
+                sources.push(BeamState {
+                    pos: Point { x, y },
+                    dir: Direction::Down,
+                });
+            }
+        }
+    }
+
+    sources
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Propagation {
+    /// Number of splitters reached by at least one beam.
+    splits: i64,
+    /// Number of cells a beam passed through.
+    energized: usize,
+    /// Number of distinct places where a beam leaves the manifold.
+    exits: usize,
+    /// Number of timelines leaving the manifold, `None` when a beam can go round in a loop.
+    timelines: Option<i64>,
+}
+
+/// Follow every beam from every `S` in any direction, through mirrors, deflectors and absorbers.
+fn propagate(manifold: &[Vec<ManifoldState>]) -> Propagation {
+    let sources = get_sources(manifold);
+    let mut seen: HashSet<BeamState> = sources.iter().copied().collect();
+    let mut queue: Vec<BeamState> = sources.clone();
+    let mut splitters: HashSet<Point> = HashSet::new();
+    let mut exits = 0;
+
+    while let Some(beam) = queue.pop() {
+        if let Some(next) = move_point(manifold, beam.pos, beam.dir)
+            && manifold[next.y][next.x] == ManifoldState::Splitter
+        {
+            splitters.insert(next);
+        }
+        for next in step(manifold, beam) {
+            match next {
+                Step::Beam(state) => {
+                    if seen.insert(state) {
+                        queue.push(state);
+                    }
+                }
+                Step::Exit => exits += 1,
+                Step::Absorbed => {}
+            }
+        }
+    }
+
+    let energized: HashSet<Point> = seen.iter().map(|beam| beam.pos).collect();
+
+    Propagation {
+        splits: splitters.len() as i64,
+        energized: energized.len(),
+        exits,
+        timelines: count_timelines(manifold, &sources),
+    }
+}
+
+/// Count the paths from the sources to an exit with an explicit stack, so tall manifolds can't
+/// overflow the call stack.  A beam reaching a state that is still being expanded means the beam
+/// can loop forever, which leaves the count undefined.
+fn count_timelines(manifold: &[Vec<ManifoldState>], sources: &[BeamState]) -> Option<i64> {
+    let mut counts: HashMap<BeamState, i64> = HashMap::new();
+    let mut in_progress: HashSet<BeamState> = HashSet::new();
     let mut total = 0;
-    let mut manifold: Vec<Vec<ManifoldState>> = input
-        .lines()
-        .map(|line| line.chars().map(get_manifold_state).collect())
-        .collect();
-
-    let y_size = manifold.len();
-    let x_size = if y_size > 0 { manifold[0].len() } else { 0 };
-
-    let starting_point = get_starting_point(&manifold);
-    manifold[starting_point.y + 1][starting_point.x] = ManifoldState::Beam;
-
-    for y in (starting_point.y + 1)..y_size {
-        for x in 0..x_size {
-            if manifold[y][x] == ManifoldState::Beam && y + 1 < y_size {
-                if manifold[y + 1][x] == ManifoldState::Empty {
-                    manifold[y + 1][x] = ManifoldState::Beam;
-                } else if manifold[y + 1][x] == ManifoldState::Splitter {
-                    total += 1;
-                    if x > 0 && manifold[y + 1][x - 1] == ManifoldState::Empty {
-                        manifold[y + 1][x - 1] = ManifoldState::Beam;
+
+    for source in sources {
+        let mut stack: Vec<(BeamState, bool)> = vec![(*source, false)];
+
+        while let Some((beam, expanded)) = stack.pop() {
+            if counts.contains_key(&beam) {
+                continue;
+            }
+            let steps = step(manifold, beam);
+            if expanded {
+                let count = steps
+                    .iter()
+                    .map(|next| match next {
+                        Step::Beam(state) => counts[state],
+                        Step::Exit => 1,
+                        Step::Absorbed => 0,
+                    })
+                    .sum();
+                counts.insert(beam, count);
+                in_progress.remove(&beam);
+                continue;
+            }
+
+            in_progress.insert(beam);
+            stack.push((beam, true));
+            for next in steps {
+                if let Step::Beam(state) = next {
+                    if in_progress.contains(&state) {
+                        return None;
                     }
-                    if x + 1 < x_size && manifold[y + 1][x + 1] == ManifoldState::Empty {
-                        manifold[y + 1][x + 1] = ManifoldState::Beam;
+                    if !counts.contains_key(&state) {
+                        stack.push((state, false));
                     }
                 }
             }
         }
+        total += counts[source];
     }
 
-    Ok(total)
+    Some(total)
 }
 
-fn part2_split(
-    manifold: &[Vec<ManifoldState>],
-    starting_point: &Point,
-    total_cache: &mut HashMap<Point, i64>,
-) -> i64 {
-    let mut total = 0;
-    let next_row = starting_point.y + 1;
+/// The row-by-row sweep only understands beams travelling down through splitters.
+fn is_downward_only(manifold: &[Vec<ManifoldState>]) -> bool {
+    get_sources(manifold).len() == 1
+        && manifold.iter().flatten().all(|state| {
+            matches!(
+                state,
+                ManifoldState::Empty
+                    | ManifoldState::Start
+                    | ManifoldState::Beam
+                    | ManifoldState::Splitter
+            )
+        })
+}
+
+/// How many timelines go through each cell of a downward-only manifold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TimelineMap {
+    start: Point,
+    /// Timelines with a beam in each cell, counted from the start.
+    arriving: Vec<Vec<i64>>,
+    /// Timelines a beam in each cell goes on to make before leaving the last row.
+    remaining: Vec<Vec<i64>>,
+}
 
-    // println!(
-    //     "Starting at: y={}, x={}",
-    //     starting_point.y, starting_point.x
-    // );
-    if let Some(&v) = total_cache.get(starting_point) {
-        return v;
+impl TimelineMap {
+    fn new(manifold: &[Vec<ManifoldState>]) -> Result<Self> {
+        if !is_downward_only(manifold) {
+            bail!("timelines can only be mapped for a single start with plain splitters");
+        }
+        let start = get_starting_point(manifold)?;
+        let x_size = manifold[0].len();
+        let y_size = manifold.len();
+        let split = |row: &[ManifoldState], counts: &[i64], x: usize| -> i64 {
+            if row[x] != ManifoldState::Splitter {
+                return counts[x];
+            }
+            let left = if x > 0 { counts[x - 1] } else { 0 };
+            left + counts.get(x + 1).copied().unwrap_or(0)
+        };
+
+        let mut arriving: Vec<Vec<i64>> = vec![vec![0; x_size]; y_size];
+        arriving[start.y][start.x] = 1;
+        for y in start.y + 1..y_size {
+            for x in 0..x_size {
+                let count = arriving[y - 1][x];
+                if manifold[y][x] == ManifoldState::Splitter {
+                    if x > 0 {
+                        arriving[y][x - 1] += count;
+                    }
+                    if x + 1 < x_size {
+                        arriving[y][x + 1] += count;
+                    }
+                } else {
+                    arriving[y][x] += count;
+                }
+            }
+        }
+
+        let mut remaining: Vec<Vec<i64>> = vec![vec![1; x_size]; y_size];
+        for y in (0..y_size - 1).rev() {
+            remaining[y] = (0..x_size)
+                .map(|x| split(&manifold[y + 1], &remaining[y + 1], x))
+                .collect();
+        }
+
+        Ok(TimelineMap {
+            start,
+            arriving,
+            remaining,
+        })
     }
 
-    if next_row >= manifold.len() {
-        return 1; // End of grid
+    fn total(&self) -> i64 {
+        self.remaining[self.start.y][self.start.x]
     }
-    if manifold[starting_point.y + 1][starting_point.x] == ManifoldState::Empty {
-        let next_point = Point {
-            x: starting_point.x,
-            y: next_row,
-        };
-        total += part2_split(manifold, &next_point, total_cache);
-    } else {
-        //Must be splitter
-        if starting_point.x > 0 {
-            let next_point = Point {
-                x: starting_point.x - 1,
-                y: next_row,
-            };
-            total += part2_split(manifold, &next_point, total_cache);
+
+    /// Timelines passing through each cell.  Every row from the start down adds up to the total.
+    fn heat(&self) -> Vec<Vec<i64>> {
+        self.arriving
+            .iter()
+            .zip(self.remaining.iter())
+            .map(|(arriving, remaining)| {
+                arriving
+                    .iter()
+                    .zip(remaining.iter())
+                    .map(|(a, r)| a * r)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Splitters ordered by how many timelines go through them, busiest first.
+    fn busiest_splitters(&self, manifold: &[Vec<ManifoldState>]) -> Vec<(Point, i64)> {
+        let mut splitters: Vec<(Point, i64)> = manifold
+            .iter()
+            .enumerate()
+            .skip(self.start.y + 1)
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, state)| **state == ManifoldState::Splitter)
+                    .map(move |(x, _)| Point { x, y })
+            })
+            .map(|p| {
+                let above = (p.y - 1, p.x);
+                (
+                    p,
+                    self.arriving[above.0][above.1] * self.remaining[above.0][above.1],
+                )
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        splitters.sort_by_key(|(p, count)| (-count, p.y, p.x));
+        splitters
+    }
+
+    /// Pick `count` timeline indices at random, repeatably for the same `seed`.
+    fn sample(&self, count: usize, seed: u64) -> Result<Vec<i64>> {
+        let total = self.total();
+        if total == 0 && count > 0 {
+            bail!("no timelines reach the last row, so none can be sampled");
+        }
+        let mut seed = seed;
+
+        Ok((0..count)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((seed >> 1) % total as u64) as i64
+            })
+            .collect())
+    }
+
+    /// The `index`th timeline as its left/right choice at each splitter, with timelines ordered
+    /// as if every `L` sorts before `R`.  This makes enumerating and sampling the same operation.
+    fn nth_timeline(&self, manifold: &[Vec<ManifoldState>], index: i64) -> Result<String> {
+        if !(0..self.total()).contains(&index) {
+            bail!("timeline {} out of range 0..{}", index, self.total());
+        }
+        let mut decisions = String::new();
+        let mut index = index;
+        let (mut x, mut y) = (self.start.x, self.start.y);
+
+        while let Some(splitter_y) =
+            (y + 1..manifold.len()).find(|&row| manifold[row][x] == ManifoldState::Splitter)
+        {
+            y = splitter_y;
+            let left = if x > 0 { self.remaining[y][x - 1] } else { 0 };
+            if index < left {
+                decisions.push('L');
+                x -= 1;
+            } else {
+                index -= left;
+                decisions.push('R');
+                x += 1;
+            }
+        }
+
+        Ok(decisions)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HeatmapFormat {
+    Ascii,
+    Ppm,
+}
+
+impl FromStr for HeatmapFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(HeatmapFormat::Ascii),
+            "ppm" => Ok(HeatmapFormat::Ppm),
+            _ => bail!("unknown heat map format: {}", s),
         }
-        if starting_point.x + 1 < manifold[0].len() {
-            let next_point = Point {
-                x: starting_point.x + 1,
-                y: next_row,
-            };
-            total += part2_split(manifold, &next_point, total_cache);
+    }
+}
+
+/// Shade every cell on a log scale so a few hot columns don't wash out the rest.  Splitters and
+/// the start keep their own characters, cells no timeline reaches stay blank.
+fn render_heatmap(
+    manifold: &[Vec<ManifoldState>],
+    heat: &[Vec<i64>],
+    format: HeatmapFormat,
+) -> String {
+    const SHADES: &[u8] = b" .:-=+*#%@";
+    let max = heat.iter().flatten().copied().max().unwrap_or(0);
+    let level = |count: i64| -> f64 {
+        match count {
+            0 => 0.0,
+            _ if max <= 1 => 1.0,
+            _ => (count as f64).ln().max(0.0) / (max as f64).ln(),
         }
+    };
+
+    let mut out = String::new();
+    if format == HeatmapFormat::Ppm {
+        out += &format!("P3\n{} {}\n255\n", manifold[0].len(), manifold.len());
     }
+    for (row, counts) in manifold.iter().zip(heat.iter()) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(counts.iter())
+            .map(|(state, &count)| match (format, state) {
+                (HeatmapFormat::Ascii, ManifoldState::Splitter) => "^".to_string(),
+                (HeatmapFormat::Ascii, ManifoldState::Start) => "S".to_string(),
+                (HeatmapFormat::Ascii, _) if count == 0 => " ".to_string(),
+                (HeatmapFormat::Ascii, _) => {
+                    let shade = 1 + (level(count) * (SHADES.len() - 2) as f64).round() as usize;
+                    (SHADES[shade] as char).to_string()
+                }
+                (HeatmapFormat::Ppm, ManifoldState::Splitter) => "0 128 255".to_string(),
+                (HeatmapFormat::Ppm, _) => {
+                    // Black through red to yellow
+                    let heat = (level(count) * 510.0).round() as i64;
+                    format!("{} {} 0", heat.min(255), (heat - 255).max(0))
+                }
+            })
+            .collect();
+        match format {
+            HeatmapFormat::Ascii => out += &format!("{}\n", cells.concat().trim_end()),
+            HeatmapFormat::Ppm => out += &format!("{}\n", cells.join(" ")),
+        }
+    }
+
+    out
+}
 
-    // println!(
-    //     "Finish total = {}, y={}, x={}",
-    //     total, starting_point.y, starting_point.x
-    // );
+fn part1(input: &str) -> Result<i64> {
+    let manifold = parse_manifold(input)?;
+    if !is_downward_only(&manifold) {
+        return Ok(propagate(&manifold).splits);
+    }
 
-    total_cache.insert(*starting_point, total);
-    total
+    Ok(sweep(&manifold)?.splits)
 }
 
 fn part2(input: &str) -> Result<i64> {
-    let manifold: Vec<Vec<ManifoldState>> = input
-        .lines()
-        .map(|line| line.chars().map(get_manifold_state).collect())
-        .collect();
-    let mut total_cache = HashMap::new();
+    let manifold = parse_manifold(input)?;
+    if !is_downward_only(&manifold) {
+        return propagate(&manifold)
+            .timelines
+            .ok_or_else(|| anyhow!("beams loop forever, so the timelines can't be counted"));
+    }
 
-    let starting_point = get_starting_point(&manifold);
+    Ok(sweep(&manifold)?.timelines.iter().sum())
+}
+
+#[derive(Debug)]
+struct Options {
+    engine: bool,
+    paths: Option<usize>,
+    sample: Option<usize>,
+    seed: u64,
+    heatmap: Option<HeatmapFormat>,
+    top: Option<usize>,
+}
+
+/// `--engine` prints the general beam engine's statistics.  For plain manifolds `--paths N` lists
+/// the first N timelines, `--sample N --seed S` picks N at random, `--heatmap ascii|ppm` renders
+/// the timelines through every cell and `--top N` lists the splitters most timelines go through.
+fn parse_args(args: &[String]) -> Result<Options> {
+    let mut options = Options {
+        engine: false,
+        paths: None,
+        sample: None,
+        seed: 1,
+        heatmap: None,
+        top: None,
+    };
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "--engine" {
+            options.engine = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| anyhow!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--paths" => options.paths = Some(value.parse()?),
+            "--sample" => options.sample = Some(value.parse()?),
+            "--seed" => options.seed = value.parse()?,
+            "--heatmap" => options.heatmap = Some(value.parse()?),
+            "--top" => options.top = Some(value.parse()?),
+            _ => bail!("unknown argument: {}", arg),
+        }
+    }
 
-    Ok(part2_split(
-        &manifold,
-        &Point {
-            x: starting_point.x,
-            y: starting_point.y + 1,
-        },
-        &mut total_cache,
-    ))
+    Ok(options)
 }
 
 fn main() -> Result<()> {
     let day = extract_day_from_exe();
     let input = read_input(day);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = parse_args(&args)?;
+
+    if let Some(format) = options.heatmap {
+        let manifold = parse_manifold(&input)?;
+        let map = TimelineMap::new(&manifold)?;
+        print!("{}", render_heatmap(&manifold, &map.heat(), format));
+        return Ok(());
+    }
 
     println!("Day {:02}", day);
+    if options.engine {
+        let result = propagate(&parse_manifold(&input)?);
+        println!("===>Splits: {}", result.splits);
+        println!("===>Energized: {}", result.energized);
+        println!("===>Exits: {}", result.exits);
+        match result.timelines {
+            Some(timelines) => println!("===>Timelines: {}", timelines),
+            None => println!("===>Timelines: beams loop forever"),
+        }
+        return Ok(());
+    }
+    if options.paths.is_some() || options.sample.is_some() || options.top.is_some() {
+        let manifold = parse_manifold(&input)?;
+        let map = TimelineMap::new(&manifold)?;
+        let mut indices: Vec<i64> =
+            (0..map.total().min(options.paths.unwrap_or(0) as i64)).collect();
+        if let Some(count) = options.sample {
+            indices.extend(map.sample(count, options.seed)?);
+        }
+        for index in indices {
+            println!(
+                "===>Timeline {}: {}",
+                index,
+                map.nth_timeline(&manifold, index)?
+            );
+        }
+        for (p, count) in map
+            .busiest_splitters(&manifold)
+            .iter()
+            .take(options.top.unwrap_or(0))
+        {
+            println!("===>Splitter at {},{}: {} timelines", p.x, p.y, count);
+        }
+        return Ok(());
+    }
     println!("===>Part 1: {}", part1(&input)?);
     println!("===>Part 2: {}", part2(&input)?);
 
@@ -170,4 +715,137 @@ mod tests {
         let input = read_example(extract_day_from_exe());
         assert_eq!(part2(&input).unwrap(), 40);
     }
+
+    #[test]
+    fn test_sweep_columns() {
+        let manifold = parse_manifold("..S..\n.....\n..^..\n.^...\n").unwrap();
+        let result = sweep(&manifold).unwrap();
+        assert_eq!(result.splits, 2);
+        assert_eq!(result.timelines, vec![1, 0, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_engine_matches_sweep() {
+        let manifold = parse_manifold(&read_example(extract_day_from_exe())).unwrap();
+        let result = propagate(&manifold);
+        let swept = sweep(&manifold).unwrap();
+        assert_eq!(result.splits, swept.splits);
+        assert_eq!(result.timelines, Some(swept.timelines.iter().sum()));
+    }
+
+    #[test]
+    fn test_mirrors_deflectors_and_loops() {
+        // Down into `\` goes right, `/` sends it up into `#`; `<` puts the second S's beam on
+        // its left, where `/` and `\` send it up and out of the top
+        let input = "S..#S\n....<\n\\../.\n";
+        let manifold = parse_manifold(input).unwrap();
+        let result = propagate(&manifold);
+        assert_eq!(result.exits, 1);
+        assert_eq!(result.timelines, Some(1));
+        assert_eq!(part2(input).unwrap(), 1);
+
+        // A beam arriving from the side passes a deflector
+        assert_eq!(part2("S...\n\\.<.\n").unwrap(), 1);
+        assert_eq!(
+            propagate(&parse_manifold("S...\n\\.<.\n").unwrap()).splits,
+            0
+        );
+
+        // `^` puts the beam into a square of mirrors that bounce it round forever
+        let input = "S....\n\\.^..\n./..\\\n.....\n.\\../\n";
+        let result = propagate(&parse_manifold(input).unwrap());
+        assert_eq!(result.timelines, None);
+        assert!(part2(input).is_err());
+    }
+
+    #[test]
+    fn test_split_beside_absorber_and_mirror() {
+        // The right-hand beam lands on `#` and is absorbed
+        let result = propagate(&parse_manifold(".S.\n.^#\n...\n").unwrap());
+        assert_eq!(result.exits, 1);
+        assert_eq!(result.timelines, Some(1));
+
+        // The right-hand beam is turned back by `/` onto the splitter, which splits it up and down
+        let result = propagate(&parse_manifold(".S..\n.^/.\n....\n....\n").unwrap());
+        assert_eq!(result.exits, 3);
+        assert_eq!(result.timelines, Some(3));
+    }
+
+    #[test]
+    fn test_malformed_manifolds() {
+        assert!(parse_manifold("").is_err());
+        assert!(part1(".....\n..^..\n").is_err());
+        assert!(part2(".....\n..^..\n").is_err());
+        assert!(parse_manifold("..S..\n..^.\n").is_err());
+        assert!(parse_manifold("..S..\n..x..\n").is_err());
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        // A start on the last row has nothing below it
+        assert_eq!(part1(".....\n..S..\n").unwrap(), 0);
+        assert_eq!(part2(".....\n..S..\n").unwrap(), 1);
+
+        // Splitters on the borders lose the beam that would leave the manifold
+        assert_eq!(part1("S...\n^...\n.^..\n").unwrap(), 2);
+        assert_eq!(part2("S...\n^...\n.^..\n").unwrap(), 2);
+        assert_eq!(part2("...S\n...^\n").unwrap(), 1);
+
+        // `|` cells are passed through rather than treated as splitters
+        assert_eq!(part1("..S..\n..|..\n..^..\n").unwrap(), 1);
+        assert_eq!(part2("..S..\n..|..\n..^..\n").unwrap(), 2);
+
+        // A second `S` is a source of its own, not a splitter
+        assert_eq!(part1("S.S\n...\n").unwrap(), 0);
+        assert_eq!(part2("S.S\n...\n").unwrap(), 2);
+        assert_eq!(part2("S..\nS..\n").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_timeline_map() {
+        let manifold = parse_manifold(&read_example(extract_day_from_exe())).unwrap();
+        let map = TimelineMap::new(&manifold).unwrap();
+        assert_eq!(map.total(), 40);
+        for row in map.heat().iter().skip(map.start.y) {
+            assert_eq!(row.iter().sum::<i64>(), 40);
+        }
+        // The first splitter below the start carries every timeline
+        assert_eq!(
+            map.busiest_splitters(&manifold)[0],
+            (Point { x: 7, y: 2 }, 40)
+        );
+
+        let paths: Vec<String> = (0..map.total())
+            .map(|index| map.nth_timeline(&manifold, index).unwrap())
+            .collect();
+        assert!(paths.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(paths[0].chars().all(|c| c == 'L'));
+        assert!(map.nth_timeline(&manifold, 40).is_err());
+    }
+
+    #[test]
+    fn test_render_heatmap() {
+        let manifold = parse_manifold("..S..\n.....\n..^..\n.....\n").unwrap();
+        let map = TimelineMap::new(&manifold).unwrap();
+        let ascii = render_heatmap(&manifold, &map.heat(), HeatmapFormat::Ascii);
+        assert_eq!(ascii, "  S\n  @\n .^.\n . .\n");
+        let ppm = render_heatmap(&manifold, &map.heat(), HeatmapFormat::Ppm);
+        assert!(ppm.starts_with("P3\n5 4\n255\n0 0 0 0 0 0 255 255 0"));
+    }
+
+    #[test]
+    fn test_sample_timelines() {
+        let manifold = parse_manifold(&read_example(extract_day_from_exe())).unwrap();
+        let map = TimelineMap::new(&manifold).unwrap();
+        let samples = map.sample(20, 7).unwrap();
+        assert_eq!(samples, map.sample(20, 7).unwrap());
+        assert!(samples.iter().all(|index| (0..40).contains(index)));
+
+        // Both beams leave a one column manifold, so there is nothing to sample
+        let manifold = parse_manifold("S\n^\n").unwrap();
+        let map = TimelineMap::new(&manifold).unwrap();
+        assert_eq!(map.total(), 0);
+        assert!(map.sample(1, 7).is_err());
+        assert!(map.sample(0, 7).unwrap().is_empty());
+    }
 }
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use aoc2025::{extract_day_from_exe, read_input};
 use std::cmp::Ordering;
 use std::{collections::BinaryHeap, str::FromStr};
@@ -43,8 +43,9 @@ impl FromStr for Point3 {
 
 struct DistBetweenPoint3 {
     dist: i64,
-    origin: Point3,
-    dest: Point3,
+    // Indices into the list of boxes
+    origin: usize,
+    dest: usize,
 }
 
 impl Ord for DistBetweenPoint3 {
@@ -67,11 +68,11 @@ fn calc_shortest_points_list(points: &[Point3], size: usize) -> BinaryHeap<DistB
 
     for (i, point) in points.iter().enumerate() {
         //Optimize by not starting at the beginning of points for second point as already calced
-        for other in points.iter().skip(i + 1) {
+        for (j, other) in points.iter().enumerate().skip(i + 1) {
             let dist = DistBetweenPoint3 {
                 dist: point.distance_int(*other),
-                origin: *point,
-                dest: *other,
+                origin: i,
+                dest: j,
             };
             list.push(dist);
             if list.len() > size {
@@ -83,68 +84,83 @@ fn calc_shortest_points_list(points: &[Point3], size: usize) -> BinaryHeap<DistB
     list
 }
 
+/// Disjoint sets of boxes, one per circuit, with path compression and union by size so each
+/// connection costs close to constant time.
+#[derive(Debug, Clone)]
+struct Circuits {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl Circuits {
+    /// Every box starts out as a circuit of its own.
+    fn new(boxes: usize) -> Self {
+        Circuits {
+            parent: (0..boxes).collect(),
+            size: vec![1; boxes],
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut i = i;
+        while self.parent[i] != root {
+            let next = self.parent[i];
+            self.parent[i] = root;
+            i = next;
+        }
+        root
+    }
+
+    /// Connect two boxes, returning false if they were already in the same circuit.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+
+    /// Size of every circuit, largest first.
+    fn sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = (0..self.parent.len())
+            .filter(|&i| self.parent[i] == i)
+            .map(|i| self.size[i])
+            .collect();
+        sizes.sort_by(|a, b| b.cmp(a));
+        sizes
+    }
+}
+
 fn calc_top_circuits(
     points_list: BinaryHeap<DistBetweenPoint3>,
-    shortest_count: usize,
+    total_boxes: usize,
     top_count: usize,
-) -> usize {
-    let mut total = 1;
-    let mut circuits: Vec<Vec<Point3>> = Vec::new();
-    let mut connection_count = shortest_count;
+) -> Result<usize> {
+    let mut circuits = Circuits::new(total_boxes);
 
     for p in points_list.into_sorted_vec().iter() {
-        // dbg!(p);
-        let mut merge_circuit_idx = None;
-        let mut found_circuit_idx = None;
-        for (i, circuit) in circuits.iter_mut().enumerate() {
-            //        for circuit in circuits.iter_mut() {
-            let origin_exists = circuit.contains(&p.origin);
-            let dest_exists = circuit.contains(&p.dest);
-            if origin_exists && dest_exists {
-                break;
-            } else if origin_exists || dest_exists {
-                if found_circuit_idx.is_some() {
-                    //If we had already found the circuit we need to merge into it
-                    merge_circuit_idx = Some(i);
-                    break;
-                }
-                connection_count -= 1;
-                found_circuit_idx = Some(i);
-                if !origin_exists {
-                    circuit.push(p.origin);
-                }
-                if !dest_exists {
-                    circuit.push(p.dest);
-                }
-            }
-        }
-        if let Some(merge_into_idx) = found_circuit_idx {
-            if let Some(idx) = merge_circuit_idx {
-                let mut to_merge = circuits.remove(idx);
-                circuits[merge_into_idx].append(&mut to_merge);
-                circuits[merge_into_idx].sort();
-                circuits[merge_into_idx].dedup();
-            }
-        } else {
-            circuits.push(vec![p.origin, p.dest]);
-            connection_count -= 1;
-        }
-        if connection_count == 0 {
-            break;
-        }
-        // dbg!(&circuits);
+        circuits.union(p.origin, p.dest);
     }
-    // dbg!(&circuits);
-
-    circuits.sort_by_key(|v| v.len());
-    for _ in 0..top_count {
-        let box_count = circuits.pop().unwrap().len();
-        dbg!(box_count);
-        total *= box_count;
-        // dbg!(total);
+
+    let sizes = circuits.sizes();
+    if sizes.len() < top_count {
+        bail!(
+            "only {} circuits, can't take the top {}",
+            sizes.len(),
+            top_count
+        );
     }
 
-    total
+    Ok(sizes.iter().take(top_count).product())
 }
 
 // shortest_count: is what number of shortest points between two points to collect
@@ -158,59 +174,48 @@ fn part1(input: &str, shortest_count: usize, circuit_count: usize) -> Result<i64
     let points_list: BinaryHeap<DistBetweenPoint3> =
         calc_shortest_points_list(&boxes, shortest_count);
 
-    let total = calc_top_circuits(points_list, shortest_count, circuit_count) as i64;
+    let total = calc_top_circuits(points_list, boxes.len(), circuit_count)? as i64;
 
     Ok(total)
 }
 
-fn calc_last_two_boxes(points_list: BinaryHeap<DistBetweenPoint3>, total_boxes: usize) -> i32 {
-    let mut total: i32 = 1;
-    let mut circuits: Vec<Vec<Point3>> = Vec::new();
+/// Prim's algorithm on the complete graph of boxes, O(n²) time and O(n) memory so no edge list is
+/// ever built.  Edges are compared like the heap orders them, distance first and then indices,
+/// which makes the tree unique and the same one Kruskal would connect.
+fn minimum_spanning_tree(boxes: &[Point3]) -> Vec<DistBetweenPoint3> {
+    let mut tree: Vec<DistBetweenPoint3> = Vec::with_capacity(boxes.len().saturating_sub(1));
+    let mut in_tree = vec![false; boxes.len()];
+    let mut best: Vec<Option<DistBetweenPoint3>> = vec![None; boxes.len()];
+    let mut next = 0;
+
+    for _ in 0..boxes.len() {
+        in_tree[next] = true;
+        if let Some(edge) = best[next] {
+            tree.push(edge);
+        }
 
-    for p in points_list.into_sorted_vec().iter() {
-        // dbg!(p);
-        let mut merge_circuit_idx = None;
-        let mut found_circuit_idx = None;
-        for (i, circuit) in circuits.iter_mut().enumerate() {
-            //        for circuit in circuits.iter_mut() {
-            let origin_exists = circuit.contains(&p.origin);
-            let dest_exists = circuit.contains(&p.dest);
-            if origin_exists && dest_exists {
-                break;
-            } else if origin_exists || dest_exists {
-                if found_circuit_idx.is_some() {
-                    //If we had already found the circuit we need to merge into it
-                    merge_circuit_idx = Some(i);
-                    break;
-                }
-                found_circuit_idx = Some(i);
-                if !origin_exists {
-                    circuit.push(p.origin);
-                }
-                if !dest_exists {
-                    circuit.push(p.dest);
-                }
+        let current = next;
+        let mut closest: Option<DistBetweenPoint3> = None;
+        for (i, other) in boxes.iter().enumerate() {
+            if in_tree[i] {
+                continue;
             }
-        }
-        if let Some(merge_into_idx) = found_circuit_idx {
-            if let Some(idx) = merge_circuit_idx {
-                let mut to_merge = circuits.remove(idx);
-                circuits[merge_into_idx].append(&mut to_merge);
-                circuits[merge_into_idx].sort();
-                circuits[merge_into_idx].dedup();
+            let edge = DistBetweenPoint3 {
+                dist: boxes[current].distance_int(*other),
+                origin: current.min(i),
+                dest: current.max(i),
+            };
+            if best[i].is_none_or(|b| edge < b) {
+                best[i] = Some(edge);
+            }
+            if closest.is_none_or(|c| best[i].unwrap() < c) {
+                closest = best[i];
+                next = i;
             }
-        } else {
-            circuits.push(vec![p.origin, p.dest]);
-        }
-        if circuits[0].len() == total_boxes {
-            total = p.origin.x * p.dest.x;
-            break;
         }
-        // dbg!(&circuits);
     }
-    // dbg!(&circuits);
 
-    total
+    tree
 }
 
 fn part2(input: &str) -> Result<i64> {
@@ -218,11 +223,13 @@ fn part2(input: &str) -> Result<i64> {
         .lines()
         .map(|s| s.parse::<Point3>().unwrap())
         .collect();
+    if boxes.len() < 2 {
+        bail!("need at least two boxes to join, found {}", boxes.len());
+    }
 
-    let points_list: BinaryHeap<DistBetweenPoint3> =
-        calc_shortest_points_list(&boxes, boxes.len() * 10); // UGLY Hack with 10 constant to throw processing power instead of elegant solution
-
-    let total = calc_last_two_boxes(points_list, boxes.len()) as i64;
+    // The longest edge of the tree is the last connection made, the one joining everything
+    let last = minimum_spanning_tree(&boxes).into_iter().max().unwrap();
+    let total = boxes[last.origin].x as i64 * boxes[last.dest].x as i64;
 
     Ok(total)
 }
@@ -254,4 +261,42 @@ mod tests {
         let input = read_example(extract_day_from_exe());
         assert_eq!(part2(&input).unwrap(), 25272);
     }
+
+    #[test]
+    fn test_circuits() {
+        let mut circuits = Circuits::new(5);
+        assert!(circuits.union(0, 1));
+        assert!(circuits.union(3, 4));
+        assert!(circuits.union(1, 4));
+        assert!(!circuits.union(0, 3));
+        assert_eq!(circuits.sizes(), vec![4, 1]);
+        assert_eq!(circuits.find(0), circuits.find(3));
+    }
+
+    #[test]
+    fn test_spanning_tree_matches_kruskal() {
+        let input = read_example(extract_day_from_exe());
+        let boxes: Vec<Point3> = input.lines().map(|s| s.parse().unwrap()).collect();
+        let tree = minimum_spanning_tree(&boxes);
+        assert_eq!(tree.len(), boxes.len() - 1);
+
+        let all_pairs = boxes.len() * (boxes.len() - 1) / 2;
+        let mut circuits = Circuits::new(boxes.len());
+        let kruskal: Vec<DistBetweenPoint3> = calc_shortest_points_list(&boxes, all_pairs)
+            .into_sorted_vec()
+            .into_iter()
+            .filter(|edge| circuits.union(edge.origin, edge.dest))
+            .collect();
+        let mut sorted = tree.clone();
+        sorted.sort();
+        assert_eq!(sorted, kruskal);
+    }
+
+    #[test]
+    fn test_part2_edge_cases() {
+        assert!(part2("").is_err());
+        assert!(part2("1,2,3").is_err());
+        // Far apart boxes that a nearest-pairs cutoff would never join
+        assert_eq!(part2("0,0,0\n1,0,0\n2,0,0\n1000,0,0").unwrap(), 2000);
+    }
 }